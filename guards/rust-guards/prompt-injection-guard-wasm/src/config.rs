@@ -1,5 +1,29 @@
 //! Configuration parsing for the prompt injection guard.
 
+use crate::rules::{Rule, RuleExpr};
+
+/// How a custom pattern is matched against scanned text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    /// Case-insensitive substring match.
+    Substring,
+    /// Regex match, compiled once when the config is parsed.
+    Regex,
+}
+
+/// An operator-supplied custom pattern.
+#[derive(Debug, Clone)]
+pub struct CustomPattern {
+    pub pattern: String,
+    pub weight: u32,
+    pub kind: MatchKind,
+    /// Compiled once at config parse time; always `Some` for `Regex`
+    /// patterns, since a pattern whose regex fails to compile is dropped
+    /// during parsing rather than carried forward to fail at scan time.
+    /// Always `None` for `Substring` patterns.
+    pub compiled: Option<regex::Regex>,
+}
+
 /// Guard configuration.
 #[derive(Debug, Clone)]
 pub struct PromptInjectionConfig {
@@ -7,14 +31,54 @@ pub struct PromptInjectionConfig {
     pub enabled_categories: Vec<String>,
     /// Total risk score threshold to trigger Deny.
     pub score_threshold: u32,
-    /// Custom patterns: (substring_pattern, weight).
-    pub custom_patterns: Vec<(String, u32)>,
+    /// Custom patterns, each either a substring or a compiled regex.
+    pub custom_patterns: Vec<CustomPattern>,
     /// Whether to scan tool invocation arguments.
     pub scan_tool_arguments: bool,
     /// Whether to scan server responses.
     pub scan_responses: bool,
     /// Maximum text length to scan (prevents DoS on large payloads).
     pub max_scan_length: usize,
+    /// Whether categories marked `fuzzy` tolerate gaps between keyword
+    /// characters (catches "i g n o r e"-style spacing evasion).
+    pub fuzzy_matching: bool,
+    /// Max non-matching characters allowed between two consecutive keyword
+    /// characters when `fuzzy_matching` is enabled.
+    pub fuzzy_match_max_gap: usize,
+    /// Additional pattern categories supplied by the host as a JSON pattern
+    /// database (see `pattern_db`), merged with the built-in `CATEGORIES` at
+    /// scan time so threat-intel updates don't require a redeploy.
+    pub loaded_categories: Vec<crate::pattern_db::LoadedCategory>,
+    /// Whether to fold Unicode confusables/homoglyphs (Cyrillic/Greek
+    /// lookalikes, fullwidth forms) to their ASCII skeleton before matching.
+    pub fold_homoglyphs: bool,
+    /// Whether to de-leetspeak text (`0→o`, `1→i`, `3→e`, `4→a`, `5→s`,
+    /// `7→t`) before matching.
+    pub deleetspeak: bool,
+    /// How many prior invocations' scores to retain per session (see
+    /// `window`) when computing the windowed score.
+    pub window_size: usize,
+    /// Per-invocation-step decay applied to a prior entry's score when
+    /// folding it into the windowed score (0.5 = roughly halves per step).
+    pub decay_factor: f64,
+    /// Deny if the windowed score (current call plus decayed history)
+    /// reaches this threshold, even when the current call alone doesn't
+    /// reach `score_threshold`. Catches payloads split across several
+    /// tool calls or responses in one conversation.
+    pub windowed_threshold: u32,
+    /// Whether to fuse an embedding-similarity score (see `semantic`) on
+    /// top of keyword matching, catching paraphrased injections that don't
+    /// match any literal pattern.
+    pub semantic_matching: bool,
+    /// Weight given to the embedding-similarity score when fusing it with
+    /// the keyword total (see `semantic::fused_bonus`).
+    pub semantic_alpha: f64,
+    /// Minimum cosine similarity to a baked-in exemplar before a semantic
+    /// match contributes to the score at all.
+    pub semantic_similarity_floor: f32,
+    /// Named boolean rules over which categories matched (see `rules`),
+    /// for conditions a single category weight can't express.
+    pub rules: Vec<Rule>,
 }
 
 impl Default for PromptInjectionConfig {
@@ -34,6 +98,18 @@ impl Default for PromptInjectionConfig {
             scan_tool_arguments: true,
             scan_responses: true,
             max_scan_length: 10000,
+            fuzzy_matching: true,
+            fuzzy_match_max_gap: 2,
+            loaded_categories: vec![],
+            fold_homoglyphs: true,
+            deleetspeak: true,
+            window_size: 5,
+            decay_factor: 0.5,
+            windowed_threshold: 5,
+            semantic_matching: false,
+            semantic_alpha: 0.5,
+            semantic_similarity_floor: 0.80,
+            rules: vec![],
         }
     }
 }
@@ -46,6 +122,18 @@ const CONFIG_KEYS: &[&str] = &[
     "enabled_categories",
     "max_scan_length",
     "custom_patterns",
+    "fuzzy_matching",
+    "fuzzy_match_max_gap",
+    "pattern_database",
+    "fold_homoglyphs",
+    "deleetspeak",
+    "window_size",
+    "decay_factor",
+    "windowed_threshold",
+    "semantic_matching",
+    "semantic_alpha",
+    "semantic_similarity_floor",
+    "rules",
 ];
 
 /// Load and parse configuration from host.
@@ -100,7 +188,23 @@ fn parse_config(val: &serde_json::Value) -> PromptInjectionConfig {
                 .filter_map(|item| {
                     let pattern = item.get("pattern")?.as_str()?.to_string();
                     let weight = item.get("weight").and_then(|w| w.as_u64()).unwrap_or(5) as u32;
-                    Some((pattern, weight))
+                    let kind = match item.get("kind").and_then(|v| v.as_str()) {
+                        Some("regex") => MatchKind::Regex,
+                        _ => MatchKind::Substring,
+                    };
+                    // A regex that fails to compile is rejected here, at
+                    // config parse time, rather than surfacing as a silent
+                    // no-op match failure on every subsequent scan.
+                    let compiled = match kind {
+                        MatchKind::Regex => Some(regex::Regex::new(&pattern).ok()?),
+                        MatchKind::Substring => None,
+                    };
+                    Some(CustomPattern {
+                        pattern,
+                        weight,
+                        kind,
+                        compiled,
+                    })
                 })
                 .collect()
         })
@@ -122,6 +226,71 @@ fn parse_config(val: &serde_json::Value) -> PromptInjectionConfig {
         .map(|v| v as usize)
         .unwrap_or(default.max_scan_length);
 
+    let fuzzy_matching = val
+        .get("fuzzy_matching")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(default.fuzzy_matching);
+
+    let fuzzy_match_max_gap = val
+        .get("fuzzy_match_max_gap")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as usize)
+        .unwrap_or(default.fuzzy_match_max_gap);
+
+    let loaded_categories = val
+        .get("pattern_database")
+        .map(crate::pattern_db::parse_pattern_database)
+        .unwrap_or(default.loaded_categories);
+
+    let fold_homoglyphs = val
+        .get("fold_homoglyphs")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(default.fold_homoglyphs);
+
+    let deleetspeak = val
+        .get("deleetspeak")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(default.deleetspeak);
+
+    let window_size = val
+        .get("window_size")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as usize)
+        .unwrap_or(default.window_size);
+
+    let decay_factor = val
+        .get("decay_factor")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(default.decay_factor);
+
+    let windowed_threshold = val
+        .get("windowed_threshold")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32)
+        .unwrap_or(default.windowed_threshold);
+
+    let semantic_matching = val
+        .get("semantic_matching")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(default.semantic_matching);
+
+    let semantic_alpha = val
+        .get("semantic_alpha")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(default.semantic_alpha);
+
+    let semantic_similarity_floor = val
+        .get("semantic_similarity_floor")
+        .and_then(|v| v.as_f64())
+        .map(|v| v as f32)
+        .unwrap_or(default.semantic_similarity_floor);
+
+    let rules = val
+        .get("rules")
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(parse_rule).collect())
+        .unwrap_or(default.rules);
+
     PromptInjectionConfig {
         enabled_categories,
         score_threshold,
@@ -129,5 +298,229 @@ fn parse_config(val: &serde_json::Value) -> PromptInjectionConfig {
         scan_tool_arguments,
         scan_responses,
         max_scan_length,
+        fuzzy_matching,
+        fuzzy_match_max_gap,
+        loaded_categories,
+        fold_homoglyphs,
+        deleetspeak,
+        window_size,
+        decay_factor,
+        windowed_threshold,
+        semantic_matching,
+        semantic_alpha,
+        semantic_similarity_floor,
+        rules,
+    }
+}
+
+/// Parse a single rule expression from its JSON form. Exactly one of
+/// `category`/`not`/`and`/`or`/`min_count` is expected; an object matching
+/// none of them, or a malformed nested expression, yields `None` so the
+/// enclosing rule is dropped at config parse time rather than silently
+/// never firing at scan time.
+fn parse_rule_expr(val: &serde_json::Value) -> Option<RuleExpr> {
+    let obj = val.as_object()?;
+
+    if let Some(category) = obj.get("category").and_then(|v| v.as_str()) {
+        return Some(RuleExpr::Category(category.to_string()));
+    }
+    if let Some(inner) = obj.get("not") {
+        return Some(RuleExpr::Not(Box::new(parse_rule_expr(inner)?)));
+    }
+    if let Some(arr) = obj.get("and").and_then(|v| v.as_array()) {
+        let exprs: Vec<RuleExpr> = arr.iter().filter_map(parse_rule_expr).collect();
+        return (!exprs.is_empty()).then_some(RuleExpr::And(exprs));
+    }
+    if let Some(arr) = obj.get("or").and_then(|v| v.as_array()) {
+        let exprs: Vec<RuleExpr> = arr.iter().filter_map(parse_rule_expr).collect();
+        return (!exprs.is_empty()).then_some(RuleExpr::Or(exprs));
+    }
+    if let Some(min_count) = obj.get("min_count").and_then(|v| v.as_object()) {
+        let categories: Vec<String> = min_count
+            .get("categories")?
+            .as_array()?
+            .iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect();
+        let count = min_count.get("count")?.as_u64()? as usize;
+        return (!categories.is_empty()).then_some(RuleExpr::MinCount { categories, count });
+    }
+
+    None
+}
+
+/// Parse a single named rule from its JSON form, dropping it if its name
+/// or condition is missing/malformed.
+fn parse_rule(item: &serde_json::Value) -> Option<Rule> {
+    let name = item.get("name")?.as_str()?.to_string();
+    let when = parse_rule_expr(item.get("when")?)?;
+    let weight = item.get("weight").and_then(|w| w.as_u64()).unwrap_or(5) as u32;
+    let hard_deny = item.get("hard_deny").and_then(|v| v.as_bool()).unwrap_or(false);
+    Some(Rule {
+        name,
+        when,
+        weight,
+        hard_deny,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_pattern_defaults_to_substring() {
+        let val = serde_json::json!({
+            "custom_patterns": [{ "pattern": "secret phrase", "weight": 7 }]
+        });
+        let config = parse_config(&val);
+        assert_eq!(config.custom_patterns.len(), 1);
+        assert_eq!(config.custom_patterns[0].kind, MatchKind::Substring);
+        assert!(config.custom_patterns[0].compiled.is_none());
+        assert_eq!(config.custom_patterns[0].weight, 7);
+    }
+
+    #[test]
+    fn test_custom_pattern_regex_kind_compiles() {
+        let val = serde_json::json!({
+            "custom_patterns": [
+                { "pattern": "(?i)ignore (all |previous )?instructions", "kind": "regex" }
+            ]
+        });
+        let config = parse_config(&val);
+        assert_eq!(config.custom_patterns.len(), 1);
+        assert_eq!(config.custom_patterns[0].kind, MatchKind::Regex);
+        assert!(config.custom_patterns[0].compiled.is_some());
+    }
+
+    #[test]
+    fn test_custom_pattern_invalid_regex_is_dropped() {
+        let val = serde_json::json!({
+            "custom_patterns": [
+                { "pattern": "(unterminated", "kind": "regex" },
+                { "pattern": "valid", "weight": 5 }
+            ]
+        });
+        let config = parse_config(&val);
+        assert_eq!(config.custom_patterns.len(), 1);
+        assert_eq!(config.custom_patterns[0].pattern, "valid");
+    }
+
+    #[test]
+    fn test_window_settings_parsed_from_config() {
+        let val = serde_json::json!({
+            "window_size": 10,
+            "decay_factor": 0.25,
+            "windowed_threshold": 12
+        });
+        let config = parse_config(&val);
+        assert_eq!(config.window_size, 10);
+        assert_eq!(config.decay_factor, 0.25);
+        assert_eq!(config.windowed_threshold, 12);
+    }
+
+    #[test]
+    fn test_window_settings_default_when_absent() {
+        let config = parse_config(&serde_json::json!({}));
+        assert_eq!(config.window_size, PromptInjectionConfig::default().window_size);
+        assert_eq!(config.decay_factor, PromptInjectionConfig::default().decay_factor);
+        assert_eq!(
+            config.windowed_threshold,
+            PromptInjectionConfig::default().windowed_threshold
+        );
+    }
+
+    #[test]
+    fn test_semantic_settings_parsed_from_config() {
+        let val = serde_json::json!({
+            "semantic_matching": true,
+            "semantic_alpha": 0.7,
+            "semantic_similarity_floor": 0.9
+        });
+        let config = parse_config(&val);
+        assert!(config.semantic_matching);
+        assert_eq!(config.semantic_alpha, 0.7);
+        assert_eq!(config.semantic_similarity_floor, 0.9);
+    }
+
+    #[test]
+    fn test_semantic_matching_defaults_to_disabled() {
+        let config = parse_config(&serde_json::json!({}));
+        assert!(!config.semantic_matching);
+    }
+
+    #[test]
+    fn test_rule_with_nested_and_or_not_parses() {
+        let val = serde_json::json!({
+            "rules": [{
+                "name": "override_plus_exfil",
+                "when": {
+                    "or": [
+                        { "and": [
+                            { "category": "prompt_override" },
+                            { "category": "data_exfiltration" }
+                        ]},
+                        { "not": { "category": "safety_bypass" } }
+                    ]
+                },
+                "weight": 10,
+                "hard_deny": true
+            }]
+        });
+        let config = parse_config(&val);
+        assert_eq!(config.rules.len(), 1);
+        assert_eq!(config.rules[0].name, "override_plus_exfil");
+        assert_eq!(config.rules[0].weight, 10);
+        assert!(config.rules[0].hard_deny);
+        assert!(matches!(config.rules[0].when, RuleExpr::Or(_)));
+    }
+
+    #[test]
+    fn test_rule_min_count_parses() {
+        let val = serde_json::json!({
+            "rules": [{
+                "name": "two_of_three",
+                "when": {
+                    "min_count": {
+                        "categories": ["prompt_override", "role_manipulation", "safety_bypass"],
+                        "count": 2
+                    }
+                }
+            }]
+        });
+        let config = parse_config(&val);
+        assert_eq!(config.rules.len(), 1);
+        match &config.rules[0].when {
+            RuleExpr::MinCount { categories, count } => {
+                assert_eq!(categories.len(), 3);
+                assert_eq!(*count, 2);
+            }
+            other => panic!("expected MinCount, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_rule_missing_when_is_dropped() {
+        let val = serde_json::json!({
+            "rules": [
+                { "name": "no_condition" },
+                { "name": "valid", "when": { "category": "prompt_override" } }
+            ]
+        });
+        let config = parse_config(&val);
+        assert_eq!(config.rules.len(), 1);
+        assert_eq!(config.rules[0].name, "valid");
+    }
+
+    #[test]
+    fn test_rule_malformed_and_branch_is_dropped() {
+        let val = serde_json::json!({
+            "rules": [{
+                "name": "bad_and",
+                "when": { "and": [] }
+            }]
+        });
+        let config = parse_config(&val);
+        assert!(config.rules.is_empty());
     }
 }
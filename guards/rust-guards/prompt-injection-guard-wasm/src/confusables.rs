@@ -0,0 +1,193 @@
+//! Confusable/homoglyph and leetspeak folding, layered on top of
+//! `patterns::normalize_text`'s lowercase/whitespace normalization.
+//!
+//! Defeats "іgnоre" (Cyrillic і/о), fullwidth "ｉｇｎｏｒｅ", and leetspeak
+//! "1gn0r3" substitutions that bypass literal keyword matching. Both passes
+//! are optional — see `PromptInjectionConfig::fold_homoglyphs` / `deleetspeak` —
+//! since folding more aggressively trades recall against false positives.
+
+use unicode_normalization::UnicodeNormalization;
+
+/// Zero-width and other invisible/format code points an attacker can splice
+/// into a keyword to break substring matching without changing what's
+/// visually rendered. Previously only counted by `patterns::has_zero_width_chars`;
+/// now stripped before matching too.
+const INVISIBLE_CHARS: &[char] = &[
+    '\u{200B}', // zero width space
+    '\u{200C}', // zero width non-joiner
+    '\u{200D}', // zero width joiner
+    '\u{FEFF}', // BOM / zero width no-break space
+    '\u{2060}', // word joiner
+    '\u{00AD}', // soft hyphen
+];
+
+/// Strip invisible/format characters so they can no longer split a keyword
+/// across a matching boundary.
+pub fn strip_invisible(text: &str) -> String {
+    strip_invisible_with_offsets(text).0
+}
+
+/// Like `strip_invisible`, but also returns, for each char kept in the
+/// output, its char index in `text` — needed to map a match found in
+/// normalized text back to a byte span in the original (see
+/// `patterns::normalize_text_with_options_and_offsets`).
+pub fn strip_invisible_with_offsets(text: &str) -> (String, Vec<usize>) {
+    let mut out = String::with_capacity(text.len());
+    let mut origins = Vec::with_capacity(text.len());
+    for (idx, ch) in text.chars().enumerate() {
+        if !INVISIBLE_CHARS.contains(&ch) {
+            out.push(ch);
+            origins.push(idx);
+        }
+    }
+    (out, origins)
+}
+
+/// Fold common Unicode confusables to their ASCII skeleton: NFKC-normalize
+/// (which already collapses fullwidth Latin forms like `ｉｇｎｏｒｅ` onto
+/// ASCII), then map each character through the confusables table to its
+/// Latin lookalike.
+pub fn fold_confusables(text: &str) -> String {
+    fold_confusables_with_offsets(text).0
+}
+
+/// Like `fold_confusables`, but also returns, for each output char, the char
+/// index in `text` of the input char it came from. NFKC is applied per
+/// input character rather than across the whole string: this misses
+/// composition that only happens between adjacent base+combining-mark
+/// pairs (not a case the confusables table below exercises), but keeps
+/// every output char anchored to exactly one source char instead of a
+/// best-guess span.
+pub fn fold_confusables_with_offsets(text: &str) -> (String, Vec<usize>) {
+    let mut out = String::with_capacity(text.len());
+    let mut origins = Vec::with_capacity(text.len());
+    for (idx, ch) in text.chars().enumerate() {
+        for folded in ch.to_string().nfkc() {
+            out.push(confusable_prototype(folded));
+            origins.push(idx);
+        }
+    }
+    (out, origins)
+}
+
+/// Map a single character to its confusables prototype. Covers the common
+/// Cyrillic/Greek lookalikes seen in prompt injection obfuscation; operators
+/// needing a broader table should extend this alongside the upstream
+/// Unicode confusablesSummary.txt data. Deliberately excludes digit/letter
+/// confusables (`0`/`o`, `1`/`l`) — that substitution class is leetspeak,
+/// folded separately by `deleetspeak` so it can be toggled independently.
+fn confusable_prototype(c: char) -> char {
+    match c {
+        // Cyrillic lookalikes.
+        '\u{0430}' => 'a', // а CYRILLIC SMALL LETTER A
+        '\u{0435}' => 'e', // е CYRILLIC SMALL LETTER IE
+        '\u{043E}' => 'o', // о CYRILLIC SMALL LETTER O
+        '\u{0440}' => 'p', // р CYRILLIC SMALL LETTER ER
+        '\u{0441}' => 'c', // с CYRILLIC SMALL LETTER ES
+        '\u{0445}' => 'x', // х CYRILLIC SMALL LETTER HA
+        '\u{0443}' => 'y', // у CYRILLIC SMALL LETTER U
+        '\u{0456}' => 'i', // і CYRILLIC SMALL LETTER BYELORUSSIAN-UKRAINIAN I
+        '\u{0458}' => 'j', // ј CYRILLIC SMALL LETTER JE
+        '\u{04BB}' => 'h', // һ CYRILLIC SMALL LETTER SHHA
+        // Greek lookalikes.
+        '\u{03BF}' => 'o', // ο GREEK SMALL LETTER OMICRON
+        '\u{03B1}' => 'a', // α GREEK SMALL LETTER ALPHA
+        '\u{03B5}' => 'e', // ε GREEK SMALL LETTER EPSILON
+        '\u{03C1}' => 'p', // ρ GREEK SMALL LETTER RHO
+        '\u{03BD}' => 'v', // ν GREEK SMALL LETTER NU
+        '\u{03C5}' => 'u', // υ GREEK SMALL LETTER UPSILON
+        _ => c,
+    }
+}
+
+/// Fold a configurable digit→letter leetspeak table: `0→o`, `1→i`, `3→e`,
+/// `4→a`, `5→s`, `7→t`. `1` is folded to `i` rather than `l`; both are
+/// plausible, and picking one deterministically is enough to catch the
+/// common "1gn0r3" style substitutions this targets.
+pub fn deleetspeak(text: &str) -> String {
+    deleetspeak_with_offsets(text).0
+}
+
+/// Like `deleetspeak`, but also returns each output char's char index in
+/// `text` (always `i` itself here, since digit substitution is one-for-one —
+/// kept as a pair for a uniform interface with the other `_with_offsets`
+/// folding passes it composes with).
+pub fn deleetspeak_with_offsets(text: &str) -> (String, Vec<usize>) {
+    let mut out = String::with_capacity(text.len());
+    let mut origins = Vec::with_capacity(text.len());
+    for (idx, ch) in text.chars().enumerate() {
+        out.push(match ch {
+            '0' => 'o',
+            '1' => 'i',
+            '3' => 'e',
+            '4' => 'a',
+            '5' => 's',
+            '7' => 't',
+            other => other,
+        });
+        origins.push(idx);
+    }
+    (out, origins)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_invisible_removes_zero_width_chars() {
+        assert_eq!(strip_invisible("ig\u{200B}nore"), "ignore");
+    }
+
+    #[test]
+    fn test_strip_invisible_leaves_normal_text_untouched() {
+        assert_eq!(strip_invisible("normal text"), "normal text");
+    }
+
+    #[test]
+    fn test_fold_confusables_cyrillic_lookalikes() {
+        assert_eq!(fold_confusables("іgnоre"), "ignore");
+    }
+
+    #[test]
+    fn test_fold_confusables_fullwidth_via_nfkc() {
+        assert_eq!(fold_confusables("ｉｇｎｏｒｅ"), "ignore");
+    }
+
+    #[test]
+    fn test_fold_confusables_does_not_touch_digits() {
+        assert_eq!(fold_confusables("1gn0r3"), "1gn0r3");
+    }
+
+    #[test]
+    fn test_deleetspeak_maps_digit_table() {
+        assert_eq!(deleetspeak("1gn0r3"), "ignore");
+    }
+
+    #[test]
+    fn test_deleetspeak_leaves_non_table_digits_and_letters_untouched() {
+        assert_eq!(deleetspeak("version2 test"), "version2 test");
+    }
+
+    #[test]
+    fn test_strip_invisible_with_offsets_points_back_to_kept_chars() {
+        let (out, origins) = strip_invisible_with_offsets("ig\u{200B}nore");
+        assert_eq!(out, "ignore");
+        // 'n' is char index 3 in the input ("i", "g", zero-width-space, "n", ...).
+        assert_eq!(origins[2], 3);
+    }
+
+    #[test]
+    fn test_fold_confusables_with_offsets_one_to_one_for_lookalikes() {
+        let (out, origins) = fold_confusables_with_offsets("іgnоre");
+        assert_eq!(out, "ignore");
+        assert_eq!(origins, vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_deleetspeak_with_offsets_is_identity_index_mapping() {
+        let (out, origins) = deleetspeak_with_offsets("1gn0r3");
+        assert_eq!(out, "ignore");
+        assert_eq!(origins, vec![0, 1, 2, 3, 4, 5]);
+    }
+}
@@ -0,0 +1,97 @@
+//! SARIF-flavored structured findings output.
+//!
+//! `lib.rs` reports each `PatternMatch` as an ad-hoc `serde_json::json!` blob
+//! in `DenyReason::details`. This renders the same matches into a small
+//! subset of the [SARIF](https://sarifweb.azurewebsites.net/) result shape
+//! instead — rule id, severity level, message, and region — so downstream
+//! tooling that already consumes SARIF from other scanners can ingest this
+//! guard's output the same way, offsets and all.
+
+use crate::patterns::PatternMatch;
+
+/// Render matches as SARIF-like `result` objects: `ruleId` is the category
+/// name, `level` is derived from weight (see `severity_for_weight`), and
+/// `locations` carries the byte region when `PatternMatch::span` is known.
+pub fn to_findings(matches: &[PatternMatch]) -> Vec<serde_json::Value> {
+    matches
+        .iter()
+        .map(|m| {
+            let mut finding = serde_json::json!({
+                "ruleId": m.category,
+                "level": severity_for_weight(m.weight),
+                "message": { "text": m.matched_text },
+            });
+            if let Some((start, end)) = m.span {
+                finding["locations"] = serde_json::json!([{
+                    "physicalLocation": {
+                        "region": { "byteOffset": start, "byteLength": end - start }
+                    }
+                }]);
+            }
+            finding
+        })
+        .collect()
+}
+
+/// Map a pattern weight to a SARIF severity level. Built-in category weights
+/// top out at 9 (`system_override`); thresholds are picked so the two
+/// highest-weighted built-in categories (8-9) read as `error`, the
+/// mid-weighted ones (5-7) as `warning`, and anything lighter (custom
+/// patterns an operator weighted low, semantic-fusion bonuses) as `note`.
+fn severity_for_weight(weight: u32) -> &'static str {
+    if weight >= 8 {
+        "error"
+    } else if weight >= 5 {
+        "warning"
+    } else {
+        "note"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern_match(weight: u32, span: Option<(usize, usize)>) -> PatternMatch {
+        PatternMatch {
+            category: "prompt_override".to_string(),
+            matched_text: "ignore previous instructions".to_string(),
+            weight,
+            span,
+        }
+    }
+
+    #[test]
+    fn test_high_weight_maps_to_error() {
+        assert_eq!(severity_for_weight(9), "error");
+        assert_eq!(severity_for_weight(8), "error");
+    }
+
+    #[test]
+    fn test_mid_weight_maps_to_warning() {
+        assert_eq!(severity_for_weight(7), "warning");
+        assert_eq!(severity_for_weight(5), "warning");
+    }
+
+    #[test]
+    fn test_low_weight_maps_to_note() {
+        assert_eq!(severity_for_weight(4), "note");
+        assert_eq!(severity_for_weight(0), "note");
+    }
+
+    #[test]
+    fn test_to_findings_includes_region_when_span_known() {
+        let findings = to_findings(&[pattern_match(8, Some((10, 20)))]);
+        assert_eq!(findings[0]["ruleId"], "prompt_override");
+        assert_eq!(findings[0]["level"], "error");
+        let region = &findings[0]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["byteOffset"], 10);
+        assert_eq!(region["byteLength"], 10);
+    }
+
+    #[test]
+    fn test_to_findings_omits_locations_when_span_unknown() {
+        let findings = to_findings(&[pattern_match(6, None)]);
+        assert!(findings[0].get("locations").is_none());
+    }
+}
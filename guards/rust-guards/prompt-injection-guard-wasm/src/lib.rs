@@ -6,11 +6,31 @@
 //! Detection phases:
 //! - tool_invoke: Scans tool arguments for injection patterns
 //! - response: Scans MCP server responses for indirect prompt injection
+//!
+//! A single call's score only reflects the text in that one call, so a
+//! payload split across several tool calls or responses in the same
+//! conversation can evade detection one call at a time. Catching that
+//! needs a stable identifier for "the same conversation" across calls;
+//! `GuardContext` carries a `session_id` field alongside `server_name` in
+//! the `security-guard` WIT world (see `wit/`, `window`, and `state`).
+//!
+//! Optional semantic scoring (see `semantic`) uses a host import,
+//! `host::embed(text: string) -> list<f32>`, alongside the existing
+//! `host::log` and `host::get_config`, so the guest can compute an
+//! embedding for scanned text without bundling its own model.
 
 mod config;
+mod confusables;
+mod decode;
+mod findings;
+mod pattern_db;
 mod patterns;
+mod rules;
 mod schema;
 mod scoring;
+mod semantic;
+mod state;
+mod window;
 
 struct PromptInjectionGuard;
 
@@ -56,14 +76,38 @@ impl Guest for PromptInjectionGuard {
             return Ok(Decision::Allow);
         }
 
-        let result = scoring::scan_text(
+        let mut result = scoring::scan_text(
             &text,
             &cfg.enabled_categories,
             &cfg.custom_patterns,
+            &cfg.loaded_categories,
             cfg.max_scan_length,
+            cfg.fuzzy_matching,
+            cfg.fuzzy_match_max_gap,
+            cfg.fold_homoglyphs,
+            cfg.deleetspeak,
+            &cfg.rules,
+        );
+        apply_semantic_fusion(&mut result, &text, &cfg);
+
+        let session_key = state::session_key(&context.server_name, &context.session_id);
+        let (windowed_score, decayed_categories) = state::record_and_window(
+            &session_key,
+            window::WindowEntry {
+                total_score: result.total_score,
+                category_scores: category_totals(&result.matches),
+            },
+            result.total_score,
+            cfg.window_size,
+            cfg.decay_factor,
         );
 
-        if result.total_score >= cfg.score_threshold {
+        let hard_deny_rule = result.triggered_rules.iter().find(|r| r.hard_deny);
+
+        if result.total_score >= cfg.score_threshold
+            || windowed_score >= cfg.windowed_threshold
+            || hard_deny_rule.is_some()
+        {
             let match_details: Vec<serde_json::Value> = result
                 .matches
                 .iter()
@@ -75,18 +119,25 @@ impl Guest for PromptInjectionGuard {
                     })
                 })
                 .collect();
+            let triggered_rule_details = triggered_rule_details(&result.triggered_rules);
 
             log_warn(&format!(
-                "Prompt injection detected in tool arguments: tool={}, server={}, score={}, threshold={}",
-                tool_name, context.server_name, result.total_score, cfg.score_threshold
+                "Prompt injection detected in tool arguments: tool={}, server={}, score={}, threshold={}, windowed_score={}, windowed_threshold={}",
+                tool_name, context.server_name, result.total_score, cfg.score_threshold, windowed_score, cfg.windowed_threshold
             ));
 
             return Ok(Decision::Deny(DenyReason {
                 code: "prompt_injection_detected".to_string(),
-                message: format!(
-                    "Prompt injection detected in tool '{}' arguments (score: {}/{})",
-                    tool_name, result.total_score, cfg.score_threshold
-                ),
+                message: match hard_deny_rule {
+                    Some(rule) => format!(
+                        "Prompt injection detected in tool '{}' arguments (rule '{}' matched)",
+                        tool_name, rule.name
+                    ),
+                    None => format!(
+                        "Prompt injection detected in tool '{}' arguments (score: {}/{}, windowed: {}/{})",
+                        tool_name, result.total_score, cfg.score_threshold, windowed_score, cfg.windowed_threshold
+                    ),
+                },
                 details: Some(
                     serde_json::json!({
                         "phase": "tool_invoke",
@@ -94,15 +145,20 @@ impl Guest for PromptInjectionGuard {
                         "server_name": context.server_name,
                         "total_score": result.total_score,
                         "threshold": cfg.score_threshold,
+                        "windowed_score": windowed_score,
+                        "windowed_threshold": cfg.windowed_threshold,
+                        "decayed_category_scores": decayed_categories,
                         "matches": match_details,
+                        "findings": findings::to_findings(&result.matches),
+                        "triggered_rules": triggered_rule_details,
                     })
                     .to_string(),
                 ),
             }));
         }
 
-        if !result.matches.is_empty() {
-            let warnings: Vec<String> = result
+        if !result.matches.is_empty() || !result.triggered_rules.is_empty() {
+            let mut warnings: Vec<String> = result
                 .matches
                 .iter()
                 .map(|m| {
@@ -112,6 +168,12 @@ impl Guest for PromptInjectionGuard {
                     )
                 })
                 .collect();
+            warnings.extend(result.triggered_rules.iter().map(|r| {
+                format!(
+                    "Composite rule matched in tool '{}' args: rule={}, weight={}",
+                    tool_name, r.name, r.weight
+                )
+            }));
             return Ok(Decision::Warn(warnings));
         }
 
@@ -138,14 +200,38 @@ impl Guest for PromptInjectionGuard {
             return Ok(Decision::Allow);
         }
 
-        let result = scoring::scan_text(
+        let mut result = scoring::scan_text(
             &text,
             &cfg.enabled_categories,
             &cfg.custom_patterns,
+            &cfg.loaded_categories,
             cfg.max_scan_length,
+            cfg.fuzzy_matching,
+            cfg.fuzzy_match_max_gap,
+            cfg.fold_homoglyphs,
+            cfg.deleetspeak,
+            &cfg.rules,
+        );
+        apply_semantic_fusion(&mut result, &text, &cfg);
+
+        let session_key = state::session_key(&context.server_name, &context.session_id);
+        let (windowed_score, decayed_categories) = state::record_and_window(
+            &session_key,
+            window::WindowEntry {
+                total_score: result.total_score,
+                category_scores: category_totals(&result.matches),
+            },
+            result.total_score,
+            cfg.window_size,
+            cfg.decay_factor,
         );
 
-        if result.total_score >= cfg.score_threshold {
+        let hard_deny_rule = result.triggered_rules.iter().find(|r| r.hard_deny);
+
+        if result.total_score >= cfg.score_threshold
+            || windowed_score >= cfg.windowed_threshold
+            || hard_deny_rule.is_some()
+        {
             let match_details: Vec<serde_json::Value> = result
                 .matches
                 .iter()
@@ -157,33 +243,45 @@ impl Guest for PromptInjectionGuard {
                     })
                 })
                 .collect();
+            let triggered_rule_details = triggered_rule_details(&result.triggered_rules);
 
             log_warn(&format!(
-                "Prompt injection detected in response: server={}, score={}, threshold={}",
-                context.server_name, result.total_score, cfg.score_threshold
+                "Prompt injection detected in response: server={}, score={}, threshold={}, windowed_score={}, windowed_threshold={}",
+                context.server_name, result.total_score, cfg.score_threshold, windowed_score, cfg.windowed_threshold
             ));
 
             return Ok(Decision::Deny(DenyReason {
                 code: "prompt_injection_in_response".to_string(),
-                message: format!(
-                    "Prompt injection detected in response from server '{}' (score: {}/{})",
-                    context.server_name, result.total_score, cfg.score_threshold
-                ),
+                message: match hard_deny_rule {
+                    Some(rule) => format!(
+                        "Prompt injection detected in response from server '{}' (rule '{}' matched)",
+                        context.server_name, rule.name
+                    ),
+                    None => format!(
+                        "Prompt injection detected in response from server '{}' (score: {}/{}, windowed: {}/{})",
+                        context.server_name, result.total_score, cfg.score_threshold, windowed_score, cfg.windowed_threshold
+                    ),
+                },
                 details: Some(
                     serde_json::json!({
                         "phase": "response",
                         "server_name": context.server_name,
                         "total_score": result.total_score,
                         "threshold": cfg.score_threshold,
+                        "windowed_score": windowed_score,
+                        "windowed_threshold": cfg.windowed_threshold,
+                        "decayed_category_scores": decayed_categories,
                         "matches": match_details,
+                        "findings": findings::to_findings(&result.matches),
+                        "triggered_rules": triggered_rule_details,
                     })
                     .to_string(),
                 ),
             }));
         }
 
-        if !result.matches.is_empty() {
-            let warnings: Vec<String> = result
+        if !result.matches.is_empty() || !result.triggered_rules.is_empty() {
+            let mut warnings: Vec<String> = result
                 .matches
                 .iter()
                 .map(|m| {
@@ -193,6 +291,12 @@ impl Guest for PromptInjectionGuard {
                     )
                 })
                 .collect();
+            warnings.extend(result.triggered_rules.iter().map(|r| {
+                format!(
+                    "Composite rule matched in response from '{}': rule={}, weight={}",
+                    context.server_name, r.name, r.weight
+                )
+            }));
             return Ok(Decision::Warn(warnings));
         }
 
@@ -211,3 +315,68 @@ impl Guest for PromptInjectionGuard {
 fn log_warn(msg: &str) {
     mcp::security_guard::host::log(3, msg);
 }
+
+/// Sum each match's weight per category, for recording into the session's
+/// rolling window (see `window`).
+fn category_totals(matches: &[patterns::PatternMatch]) -> Vec<(String, u32)> {
+    let mut totals: Vec<(String, u32)> = Vec::new();
+    for m in matches {
+        match totals.iter_mut().find(|(category, _)| *category == m.category) {
+            Some((_, total)) => *total += m.weight,
+            None => totals.push((m.category.clone(), m.weight)),
+        }
+    }
+    totals
+}
+
+/// Render triggered composite rules (see `rules`) into the same `details`
+/// JSON shape as pattern matches, so operators can see which named boolean
+/// condition fired alongside the categories that fed it.
+fn triggered_rule_details(triggered: &[rules::TriggeredRule]) -> Vec<serde_json::Value> {
+    triggered
+        .iter()
+        .map(|r| {
+            serde_json::json!({
+                "name": r.name,
+                "weight": r.weight,
+                "hard_deny": r.hard_deny,
+            })
+        })
+        .collect()
+}
+
+/// If semantic matching is enabled, embed `text` via the host, find the
+/// closest baked-in exemplar (see `semantic`), and fold its fused bonus
+/// into `result` as an extra `PatternMatch` so it's explainable the same
+/// way a keyword match is — which exemplar it was closest to, and how
+/// similar.
+fn apply_semantic_fusion(
+    result: &mut scoring::ScanResult,
+    text: &str,
+    cfg: &config::PromptInjectionConfig,
+) {
+    if !cfg.semantic_matching {
+        return;
+    }
+
+    let embedding = mcp::security_guard::host::embed(text);
+    let Some(matched) = semantic::best_match(&embedding) else {
+        return;
+    };
+    let Some(bonus) = semantic::fused_bonus(&matched, cfg.semantic_alpha, cfg.semantic_similarity_floor)
+    else {
+        return;
+    };
+
+    result.matches.push(patterns::PatternMatch {
+        category: matched.category.to_string(),
+        matched_text: format!(
+            "(semantic match to \"{}\", similarity {:.2})",
+            matched.exemplar_phrase, matched.similarity
+        ),
+        weight: bonus,
+        // An embedding similarity match has no literal span of its own.
+        span: None,
+    });
+    result.total_score = result.total_score.saturating_add(bonus);
+}
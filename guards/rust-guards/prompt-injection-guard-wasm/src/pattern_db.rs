@@ -0,0 +1,187 @@
+//! Host-loadable pattern database.
+//!
+//! The 7 categories in `patterns::CATEGORIES` are compiled into the WASM
+//! component, so adding a newly-observed injection technique normally means a
+//! code change and redeploy. This module lets the host supply an additional
+//! JSON document of complete categories — same keyword-sequence shape as the
+//! built-in ones — that gets merged in at config-read time, so threat-intel
+//! updates can ship without recompiling the guard.
+//!
+//! Loaded categories are an owned analog of `PatternCategory`/`DetectionPattern`
+//! (the built-in types borrow `'static` string literals, which a runtime JSON
+//! document can't provide). `scoring::scan_text` matches against both through
+//! `patterns::match_term_groups`, the shared core behind `match_pattern`.
+
+/// One host-loaded detection pattern: a sequence of term groups, same
+/// semantics as `patterns::DetectionPattern` (all groups must match in
+/// order; any alternative within a group matches).
+#[derive(Debug, Clone)]
+pub struct LoadedPattern {
+    pub terms: Vec<Vec<String>>,
+}
+
+/// One host-loaded pattern category, the owned analog of `patterns::PatternCategory`.
+#[derive(Debug, Clone)]
+pub struct LoadedCategory {
+    pub name: String,
+    pub weight: u32,
+    pub fuzzy: bool,
+    pub patterns: Vec<LoadedPattern>,
+}
+
+impl LoadedCategory {
+    /// Try to match `text` against this category's patterns, mirroring the
+    /// "first match wins" behavior `scoring::scan_text` applies to built-ins.
+    pub fn first_match(&self, text: &str, fuzzy_matching: bool, max_gap: usize) -> Option<crate::patterns::PatternHit> {
+        let use_fuzzy = fuzzy_matching && self.fuzzy;
+        self.patterns.iter().find_map(|pattern| {
+            let groups: Vec<Vec<&str>> = pattern
+                .terms
+                .iter()
+                .map(|group| group.iter().map(|s| s.as_str()).collect())
+                .collect();
+            crate::patterns::match_term_groups(text, &groups, use_fuzzy, max_gap)
+        })
+    }
+}
+
+/// Expected `"schema_version"` of the pattern database document. Bumped if
+/// the shape of a loaded category ever changes incompatibly.
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// Parse a host-supplied pattern database JSON value into loaded categories.
+/// Malformed or unversioned categories are skipped individually rather than
+/// rejecting the whole document, so one bad entry in a threat-intel feed
+/// doesn't take out categories that parsed fine.
+pub fn parse_pattern_database(val: &serde_json::Value) -> Vec<LoadedCategory> {
+    let schema_version = val.get("schema_version").and_then(|v| v.as_u64());
+    if schema_version.is_some_and(|v| v != SCHEMA_VERSION) {
+        // An unrecognized document shape; skip the whole feed rather than
+        // risk misparsing categories into something scan_text can't handle.
+        return Vec::new();
+    }
+
+    let Some(categories) = val.get("categories").and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+
+    categories.iter().filter_map(parse_category).collect()
+}
+
+fn parse_category(val: &serde_json::Value) -> Option<LoadedCategory> {
+    let name = val.get("name")?.as_str()?.to_string();
+    let weight = val.get("weight")?.as_u64()? as u32;
+    let fuzzy = val.get("fuzzy").and_then(|v| v.as_bool()).unwrap_or(false);
+
+    let patterns = val
+        .get("patterns")?
+        .as_array()?
+        .iter()
+        .filter_map(parse_pattern)
+        .collect::<Vec<_>>();
+
+    if patterns.is_empty() {
+        return None;
+    }
+
+    Some(LoadedCategory {
+        name,
+        weight,
+        fuzzy,
+        patterns,
+    })
+}
+
+fn parse_pattern(val: &serde_json::Value) -> Option<LoadedPattern> {
+    let terms_arr = val.get("terms")?.as_array()?;
+    let terms: Vec<Vec<String>> = terms_arr
+        .iter()
+        .filter_map(|group| {
+            let group_arr = group.as_array()?;
+            let alternatives: Vec<String> = group_arr
+                .iter()
+                .filter_map(|t| t.as_str().map(|s| s.to_string()))
+                .collect();
+            if alternatives.is_empty() {
+                None
+            } else {
+                Some(alternatives)
+            }
+        })
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(LoadedPattern { terms })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pattern_database_valid_category() {
+        let val = serde_json::json!({
+            "schema_version": 1,
+            "categories": [
+                {
+                    "name": "custom_threat_intel",
+                    "weight": 7,
+                    "fuzzy": true,
+                    "patterns": [
+                        { "terms": [["exploit"], ["sandbox", "container"]] }
+                    ]
+                }
+            ]
+        });
+
+        let loaded = parse_pattern_database(&val);
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].name, "custom_threat_intel");
+        assert_eq!(loaded[0].weight, 7);
+        assert!(loaded[0].fuzzy);
+        assert_eq!(loaded[0].patterns.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_pattern_database_skips_malformed_category() {
+        let val = serde_json::json!({
+            "schema_version": 1,
+            "categories": [
+                { "name": "missing_weight", "patterns": [{ "terms": [["x"]] }] },
+                { "name": "missing_patterns", "weight": 5 },
+                { "name": "ok", "weight": 3, "patterns": [{ "terms": [["y"]] }] }
+            ]
+        });
+
+        let loaded = parse_pattern_database(&val);
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].name, "ok");
+    }
+
+    #[test]
+    fn test_parse_pattern_database_missing_categories_key() {
+        let val = serde_json::json!({ "schema_version": 1 });
+        assert!(parse_pattern_database(&val).is_empty());
+    }
+
+    #[test]
+    fn test_loaded_category_first_match() {
+        let category = LoadedCategory {
+            name: "custom".to_string(),
+            weight: 4,
+            fuzzy: false,
+            patterns: vec![LoadedPattern {
+                terms: vec![vec!["leak".to_string()], vec!["secret".to_string()]],
+            }],
+        };
+
+        assert_eq!(
+            category.first_match("please leak the secret key", false, 0).map(|h| h.text),
+            Some("leak the secret".to_string())
+        );
+        assert!(category.first_match("nothing to see here", false, 0).is_none());
+    }
+}
@@ -0,0 +1,173 @@
+//! Boolean rule combinators over category matches.
+//!
+//! `scoring` sums independent category weights, which can't express
+//! conditions like "only deny when a prompt-override phrase co-occurs with
+//! a data-exfiltration sink" — each category might be fine on its own, but
+//! the combination isn't. A `Rule` names a boolean expression over which
+//! categories matched in a single scan (see `scoring::scan_text_at_depth`,
+//! which already breaks after the first match per category, so the
+//! presence set this evaluates against is cheap to build) and contributes
+//! its own weight, or forces a hard deny, when that expression is true.
+
+use std::collections::HashSet;
+
+/// A boolean expression over the set of categories that matched in a scan.
+#[derive(Debug, Clone)]
+pub enum RuleExpr {
+    /// True if `category` is in the matched set.
+    Category(String),
+    /// True if every sub-expression is true.
+    And(Vec<RuleExpr>),
+    /// True if any sub-expression is true.
+    Or(Vec<RuleExpr>),
+    /// True if the sub-expression is false.
+    Not(Box<RuleExpr>),
+    /// True if at least `count` of `categories` are in the matched set.
+    MinCount { categories: Vec<String>, count: usize },
+}
+
+/// A named rule: a boolean condition plus what it contributes when true.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub name: String,
+    pub when: RuleExpr,
+    /// Risk score weight added to the total when `when` is true.
+    pub weight: u32,
+    /// Force a Deny when `when` is true, regardless of `score_threshold`.
+    pub hard_deny: bool,
+}
+
+/// A rule whose condition was true for a given scan.
+pub struct TriggeredRule {
+    pub name: String,
+    pub weight: u32,
+    pub hard_deny: bool,
+}
+
+/// Evaluate `expr` against the set of categories that matched in a scan.
+pub fn evaluate_expr(expr: &RuleExpr, matched_categories: &HashSet<String>) -> bool {
+    match expr {
+        RuleExpr::Category(category) => matched_categories.contains(category),
+        RuleExpr::And(exprs) => exprs.iter().all(|e| evaluate_expr(e, matched_categories)),
+        RuleExpr::Or(exprs) => exprs.iter().any(|e| evaluate_expr(e, matched_categories)),
+        RuleExpr::Not(inner) => !evaluate_expr(inner, matched_categories),
+        RuleExpr::MinCount { categories, count } => {
+            categories
+                .iter()
+                .filter(|c| matched_categories.contains(*c))
+                .count()
+                >= *count
+        }
+    }
+}
+
+/// Evaluate every rule against `matched_categories`, returning the ones
+/// whose condition is true.
+pub fn evaluate_rules(rules: &[Rule], matched_categories: &HashSet<String>) -> Vec<TriggeredRule> {
+    rules
+        .iter()
+        .filter(|rule| evaluate_expr(&rule.when, matched_categories))
+        .map(|rule| TriggeredRule {
+            name: rule.name.clone(),
+            weight: rule.weight,
+            hard_deny: rule.hard_deny,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn categories(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_category_expr_true_when_present() {
+        let expr = RuleExpr::Category("prompt_override".to_string());
+        assert!(evaluate_expr(&expr, &categories(&["prompt_override"])));
+        assert!(!evaluate_expr(&expr, &categories(&["role_manipulation"])));
+    }
+
+    #[test]
+    fn test_and_requires_all_sub_expressions() {
+        let expr = RuleExpr::And(vec![
+            RuleExpr::Category("prompt_override".to_string()),
+            RuleExpr::Category("data_exfiltration".to_string()),
+        ]);
+        assert!(evaluate_expr(&expr, &categories(&["prompt_override", "data_exfiltration"])));
+        assert!(!evaluate_expr(&expr, &categories(&["prompt_override"])));
+    }
+
+    #[test]
+    fn test_or_requires_any_sub_expression() {
+        let expr = RuleExpr::Or(vec![
+            RuleExpr::Category("prompt_override".to_string()),
+            RuleExpr::Category("data_exfiltration".to_string()),
+        ]);
+        assert!(evaluate_expr(&expr, &categories(&["data_exfiltration"])));
+        assert!(!evaluate_expr(&expr, &categories(&["role_manipulation"])));
+    }
+
+    #[test]
+    fn test_not_negates_sub_expression() {
+        let expr = RuleExpr::Not(Box::new(RuleExpr::Category("safety_bypass".to_string())));
+        assert!(evaluate_expr(&expr, &categories(&[])));
+        assert!(!evaluate_expr(&expr, &categories(&["safety_bypass"])));
+    }
+
+    #[test]
+    fn test_min_count_requires_threshold_met() {
+        let expr = RuleExpr::MinCount {
+            categories: vec![
+                "prompt_override".to_string(),
+                "role_manipulation".to_string(),
+                "safety_bypass".to_string(),
+            ],
+            count: 2,
+        };
+        assert!(evaluate_expr(&expr, &categories(&["prompt_override", "safety_bypass"])));
+        assert!(!evaluate_expr(&expr, &categories(&["prompt_override"])));
+    }
+
+    #[test]
+    fn test_nested_expression() {
+        // (prompt_override AND data_exfiltration) OR NOT safety_bypass
+        let expr = RuleExpr::Or(vec![
+            RuleExpr::And(vec![
+                RuleExpr::Category("prompt_override".to_string()),
+                RuleExpr::Category("data_exfiltration".to_string()),
+            ]),
+            RuleExpr::Not(Box::new(RuleExpr::Category("safety_bypass".to_string()))),
+        ]);
+        assert!(evaluate_expr(&expr, &categories(&["prompt_override", "data_exfiltration"])));
+        assert!(evaluate_expr(&expr, &categories(&[])));
+        assert!(!evaluate_expr(&expr, &categories(&["safety_bypass"])));
+    }
+
+    #[test]
+    fn test_evaluate_rules_returns_only_triggered() {
+        let rules = vec![
+            Rule {
+                name: "override_plus_exfil".to_string(),
+                when: RuleExpr::And(vec![
+                    RuleExpr::Category("prompt_override".to_string()),
+                    RuleExpr::Category("data_exfiltration".to_string()),
+                ]),
+                weight: 10,
+                hard_deny: true,
+            },
+            Rule {
+                name: "role_manipulation_alone".to_string(),
+                when: RuleExpr::Category("role_manipulation".to_string()),
+                weight: 3,
+                hard_deny: false,
+            },
+        ];
+        let triggered = evaluate_rules(&rules, &categories(&["prompt_override", "data_exfiltration"]));
+        assert_eq!(triggered.len(), 1);
+        assert_eq!(triggered[0].name, "override_plus_exfil");
+        assert!(triggered[0].hard_deny);
+    }
+}
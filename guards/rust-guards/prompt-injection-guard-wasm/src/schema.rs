@@ -86,14 +86,21 @@ pub fn get_settings_schema() -> String {
             "custom_patterns": {
                 "type": "array",
                 "title": "Custom Patterns",
-                "description": "Additional substring patterns to detect (case-insensitive)",
+                "description": "Additional patterns to detect, as a case-insensitive substring or a regex",
                 "items": {
                     "type": "object",
                     "properties": {
                         "pattern": {
                             "type": "string",
                             "title": "Pattern",
-                            "description": "Case-insensitive substring to match"
+                            "description": "Case-insensitive substring, or a regex when kind is \"regex\" (first capture group is reported if present, otherwise the whole match)"
+                        },
+                        "kind": {
+                            "type": "string",
+                            "title": "Kind",
+                            "description": "How to interpret the pattern",
+                            "enum": ["substring", "regex"],
+                            "default": "substring"
                         },
                         "weight": {
                             "type": "integer",
@@ -112,6 +119,203 @@ pub fn get_settings_schema() -> String {
                     "placeholder": "Add custom pattern",
                     "order": 6
                 }
+            },
+            "fuzzy_matching": {
+                "type": "boolean",
+                "title": "Fuzzy Matching",
+                "description": "Tolerate small gaps between keyword characters in fuzzy-enabled categories, catching spacing/junk-char evasion like 'i g n o r e'",
+                "default": true,
+                "x-ui": {
+                    "component": "checkbox",
+                    "order": 7
+                }
+            },
+            "fuzzy_match_max_gap": {
+                "type": "integer",
+                "title": "Fuzzy Match Max Gap",
+                "description": "Maximum non-matching characters tolerated between two consecutive keyword characters when fuzzy matching is enabled",
+                "default": 2,
+                "minimum": 0,
+                "maximum": 10,
+                "x-ui": {
+                    "component": "input",
+                    "order": 8
+                }
+            },
+            "pattern_database": {
+                "type": "object",
+                "title": "Pattern Database",
+                "description": "Additional detection categories loaded from a host-supplied threat-intel feed, merged with the built-in categories without requiring a guard redeploy",
+                "properties": {
+                    "schema_version": {
+                        "type": "integer",
+                        "description": "Version of the pattern database document shape"
+                    },
+                    "categories": {
+                        "type": "array",
+                        "description": "Category definitions: name, weight, optional fuzzy flag, and patterns (each a sequence of term groups)",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "name": { "type": "string" },
+                                "weight": { "type": "integer" },
+                                "fuzzy": { "type": "boolean" },
+                                "patterns": {
+                                    "type": "array",
+                                    "items": {
+                                        "type": "object",
+                                        "properties": {
+                                            "terms": {
+                                                "type": "array",
+                                                "items": {
+                                                    "type": "array",
+                                                    "items": { "type": "string" }
+                                                }
+                                            }
+                                        },
+                                        "required": ["terms"]
+                                    }
+                                }
+                            },
+                            "required": ["name", "weight", "patterns"]
+                        }
+                    }
+                },
+                "default": { "schema_version": 1, "categories": [] },
+                "x-ui": {
+                    "component": "json-editor",
+                    "order": 9
+                }
+            },
+            "fold_homoglyphs": {
+                "type": "boolean",
+                "title": "Fold Homoglyphs",
+                "description": "Fold Unicode confusables (Cyrillic/Greek lookalikes, fullwidth forms) to their ASCII skeleton before matching, catching e.g. Cyrillic 'іgnоre'",
+                "default": true,
+                "x-ui": {
+                    "component": "checkbox",
+                    "order": 10
+                }
+            },
+            "deleetspeak": {
+                "type": "boolean",
+                "title": "De-leetspeak",
+                "description": "Fold common leetspeak digit substitutions (0→o, 1→i, 3→e, 4→a, 5→s, 7→t) before matching, catching e.g. '1gn0r3'",
+                "default": true,
+                "x-ui": {
+                    "component": "checkbox",
+                    "order": 11
+                }
+            },
+            "window_size": {
+                "type": "integer",
+                "title": "Window Size",
+                "description": "How many prior tool calls/responses in the same conversation to retain when computing the windowed score, catching payloads split across several calls",
+                "default": 5,
+                "minimum": 1,
+                "maximum": 50,
+                "x-ui": {
+                    "component": "number",
+                    "order": 12
+                }
+            },
+            "decay_factor": {
+                "type": "number",
+                "title": "Decay Factor",
+                "description": "Per-step decay applied to a prior call's score when folding it into the windowed score (0.5 roughly halves a contribution each step back)",
+                "default": 0.5,
+                "minimum": 0.0,
+                "maximum": 1.0,
+                "x-ui": {
+                    "component": "number",
+                    "order": 13
+                }
+            },
+            "windowed_threshold": {
+                "type": "integer",
+                "title": "Windowed Threshold",
+                "description": "Deny if the windowed score (current call plus decayed history) reaches this threshold, even when the current call alone is below Score Threshold",
+                "default": 5,
+                "minimum": 1,
+                "x-ui": {
+                    "component": "number",
+                    "order": 14
+                }
+            },
+            "semantic_matching": {
+                "type": "boolean",
+                "title": "Semantic Matching",
+                "description": "Fuse an embedding-similarity score against known injection phrases on top of keyword matching, catching paraphrases that don't match any literal pattern",
+                "default": false,
+                "x-ui": {
+                    "component": "checkbox",
+                    "order": 15
+                }
+            },
+            "semantic_alpha": {
+                "type": "number",
+                "title": "Semantic Alpha",
+                "description": "Weight given to the embedding-similarity score when fusing it with the keyword total",
+                "default": 0.5,
+                "minimum": 0.0,
+                "maximum": 1.0,
+                "x-ui": {
+                    "component": "number",
+                    "order": 16
+                }
+            },
+            "semantic_similarity_floor": {
+                "type": "number",
+                "title": "Semantic Similarity Floor",
+                "description": "Minimum cosine similarity to a known injection phrase before a semantic match contributes to the score at all",
+                "default": 0.80,
+                "minimum": 0.0,
+                "maximum": 1.0,
+                "x-ui": {
+                    "component": "number",
+                    "order": 17
+                }
+            },
+            "rules": {
+                "type": "array",
+                "title": "Composite Rules",
+                "description": "Named boolean rules over which categories matched (AND/OR/NOT/min-count), for conditions a single category weight can't express, e.g. a prompt-override phrase co-occurring with a data-exfiltration sink",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "name": {
+                            "type": "string",
+                            "title": "Name",
+                            "description": "Identifies this rule in deny/warn details"
+                        },
+                        "when": {
+                            "type": "object",
+                            "title": "Condition",
+                            "description": "One of category, not, and, or, min_count, e.g. {\"and\": [{\"category\": \"prompt_override\"}, {\"category\": \"data_exfiltration\"}]}"
+                        },
+                        "weight": {
+                            "type": "integer",
+                            "title": "Weight",
+                            "description": "Risk score weight added to the total when the condition is true",
+                            "default": 5,
+                            "minimum": 0,
+                            "maximum": 50
+                        },
+                        "hard_deny": {
+                            "type": "boolean",
+                            "title": "Hard Deny",
+                            "description": "Force a Deny when the condition is true, regardless of Score Threshold",
+                            "default": false
+                        }
+                    },
+                    "required": ["name", "when"]
+                },
+                "default": [],
+                "x-ui": {
+                    "component": "object-array",
+                    "placeholder": "Add composite rule",
+                    "order": 18
+                }
             }
         },
         "x-guard-meta": {
@@ -141,7 +345,19 @@ pub fn get_default_config() -> String {
         ],
         "score_threshold": 5,
         "max_scan_length": 10000,
-        "custom_patterns": []
+        "custom_patterns": [],
+        "fuzzy_matching": true,
+        "fuzzy_match_max_gap": 2,
+        "pattern_database": { "schema_version": 1, "categories": [] },
+        "fold_homoglyphs": true,
+        "deleetspeak": true,
+        "window_size": 5,
+        "decay_factor": 0.5,
+        "windowed_threshold": 5,
+        "semantic_matching": false,
+        "semantic_alpha": 0.5,
+        "semantic_similarity_floor": 0.80,
+        "rules": []
     })
     .to_string()
 }
@@ -0,0 +1,147 @@
+//! Session-scoped storage for cross-invocation scoring.
+//!
+//! Keyed by server name + session identifier (see `window`) so a window is
+//! shared across every tool call and response within one conversation but
+//! never leaks across sessions or servers.
+//!
+//! Session identifiers are client-supplied, so a long-running gateway
+//! instance that recorded a new `SessionWindow` per distinct one forever
+//! would grow without bound. `SessionStore` caps the number of sessions it
+//! tracks at once and evicts the least-recently-touched one to make room
+//! for a new one once the cap is hit.
+
+use crate::window::{SessionWindow, WindowEntry};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+
+/// Hard cap on distinct sessions tracked at once.
+const MAX_TRACKED_SESSIONS: usize = 10_000;
+
+/// A bounded collection of session windows with least-recently-touched
+/// eviction. Kept as a plain struct, independent of `thread_local!`, so the
+/// eviction logic can be unit tested directly.
+#[derive(Default)]
+struct SessionStore {
+    windows: HashMap<String, SessionWindow>,
+    /// Recency order, oldest-touched at the front. Never contains a key
+    /// more than once: touching an existing key removes and re-appends it.
+    lru_order: VecDeque<String>,
+    capacity: usize,
+}
+
+impl SessionStore {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            windows: HashMap::new(),
+            lru_order: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    fn record_and_window(
+        &mut self,
+        key: &str,
+        entry: WindowEntry,
+        current_score: u32,
+        window_size: usize,
+        decay_factor: f64,
+    ) -> (u32, Vec<(String, u32)>) {
+        self.touch(key);
+        if !self.windows.contains_key(key) && self.windows.len() >= self.capacity {
+            if let Some(evicted) = self.lru_order.pop_front() {
+                self.windows.remove(&evicted);
+            }
+        }
+
+        let window = self.windows.entry(key.to_string()).or_default();
+        let windowed_score = window.windowed_score(current_score, decay_factor);
+        let decayed_categories = window.decayed_category_scores(decay_factor);
+        window.record(entry, window_size);
+        (windowed_score, decayed_categories)
+    }
+
+    /// Mark `key` as most-recently-touched, moving it to the back of
+    /// `lru_order` (inserting it if this is its first touch).
+    fn touch(&mut self, key: &str) {
+        if let Some(pos) = self.lru_order.iter().position(|k| k == key) {
+            self.lru_order.remove(pos);
+        }
+        self.lru_order.push_back(key.to_string());
+    }
+}
+
+thread_local! {
+    static SESSION_WINDOWS: RefCell<SessionStore> =
+        RefCell::new(SessionStore::with_capacity(MAX_TRACKED_SESSIONS));
+}
+
+/// Build the key a session's window is stored under.
+pub fn session_key(server_name: &str, session_id: &str) -> String {
+    format!("{server_name}:{session_id}")
+}
+
+/// Record `entry` into the named session's window, then return the
+/// windowed score for `current_score` (the entry's own score, not yet
+/// folded into the stored history) plus the decayed per-category totals
+/// accumulated so far.
+pub fn record_and_window(
+    key: &str,
+    entry: WindowEntry,
+    current_score: u32,
+    window_size: usize,
+    decay_factor: f64,
+) -> (u32, Vec<(String, u32)>) {
+    SESSION_WINDOWS.with(|store| {
+        store
+            .borrow_mut()
+            .record_and_window(key, entry, current_score, window_size, decay_factor)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u32) -> WindowEntry {
+        WindowEntry {
+            total_score: score,
+            category_scores: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_under_capacity_keeps_every_session() {
+        let mut store = SessionStore::with_capacity(10);
+        for i in 0..5 {
+            store.record_and_window(&format!("session-{i}"), entry(1), 1, 5, 0.5);
+        }
+        assert_eq!(store.windows.len(), 5);
+    }
+
+    #[test]
+    fn test_over_capacity_evicts_least_recently_touched() {
+        let mut store = SessionStore::with_capacity(2);
+        store.record_and_window("a", entry(1), 1, 5, 0.5);
+        store.record_and_window("b", entry(1), 1, 5, 0.5);
+        store.record_and_window("c", entry(1), 1, 5, 0.5);
+
+        assert_eq!(store.windows.len(), 2);
+        assert!(!store.windows.contains_key("a"));
+        assert!(store.windows.contains_key("b"));
+        assert!(store.windows.contains_key("c"));
+    }
+
+    #[test]
+    fn test_touching_an_existing_session_protects_it_from_eviction() {
+        let mut store = SessionStore::with_capacity(2);
+        store.record_and_window("a", entry(1), 1, 5, 0.5);
+        store.record_and_window("b", entry(1), 1, 5, 0.5);
+        // Re-touch "a" so "b" becomes the least-recently-touched session.
+        store.record_and_window("a", entry(1), 1, 5, 0.5);
+        store.record_and_window("c", entry(1), 1, 5, 0.5);
+
+        assert!(store.windows.contains_key("a"));
+        assert!(!store.windows.contains_key("b"));
+        assert!(store.windows.contains_key("c"));
+    }
+}
@@ -0,0 +1,132 @@
+//! Session-scoped rolling window for cross-invocation scoring.
+//!
+//! `scan_text` only ever sees one tool call or response at a time, so an
+//! attacker can split a payload across several invocations in the same
+//! conversation, each individually scoring well under `score_threshold`.
+//! A `SessionWindow` keeps a bounded, decaying history of recent
+//! invocation scores so a chain of small scores can still cross a separate
+//! `windowed_threshold` even though no single call did.
+
+use std::collections::VecDeque;
+
+/// One invocation's contribution to a session's window.
+#[derive(Debug, Clone)]
+pub struct WindowEntry {
+    pub total_score: u32,
+    /// Per-category breakdown, carried along so a windowed deny's `details`
+    /// can show which categories contributed across the window, not just
+    /// the current call.
+    pub category_scores: Vec<(String, u32)>,
+}
+
+/// A session's rolling window: a bounded ring buffer of recent invocations,
+/// oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct SessionWindow {
+    entries: VecDeque<WindowEntry>,
+}
+
+impl SessionWindow {
+    /// Record `entry` as the most recent invocation, evicting the oldest
+    /// entry once the window exceeds `window_size`.
+    pub fn record(&mut self, entry: WindowEntry, window_size: usize) {
+        self.entries.push_back(entry);
+        while self.entries.len() > window_size {
+            self.entries.pop_front();
+        }
+    }
+
+    /// `current_score` plus the decayed sum of every entry already recorded
+    /// in the window. Each entry's contribution is multiplied by
+    /// `decay_factor` once per invocation that has happened since it was
+    /// recorded, so the most recent prior entry is scaled by `decay_factor`,
+    /// the one before that by `decay_factor^2`, and so on — at the default
+    /// `decay_factor` of 0.5, a contribution roughly halves each step back.
+    pub fn windowed_score(&self, current_score: u32, decay_factor: f64) -> u32 {
+        let decayed_sum: f64 = self
+            .entries
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(steps_ago, entry)| entry.total_score as f64 * decay_factor.powi(steps_ago as i32 + 1))
+            .sum();
+        current_score.saturating_add(decayed_sum.round() as u32)
+    }
+
+    /// Decayed per-category totals across the window, for surfacing in
+    /// `details` alongside the current call's own category scores.
+    pub fn decayed_category_scores(&self, decay_factor: f64) -> Vec<(String, u32)> {
+        let mut totals: Vec<(String, u32)> = Vec::new();
+        for (steps_ago, entry) in self.entries.iter().rev().enumerate() {
+            let decay = decay_factor.powi(steps_ago as i32 + 1);
+            for (category, score) in &entry.category_scores {
+                let decayed = (*score as f64 * decay).round() as u32;
+                match totals.iter_mut().find(|(c, _)| c == category) {
+                    Some((_, total)) => *total += decayed,
+                    None => totals.push((category.clone(), decayed)),
+                }
+            }
+        }
+        totals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(total_score: u32) -> WindowEntry {
+        WindowEntry {
+            total_score,
+            category_scores: vec![("prompt_override".to_string(), total_score)],
+        }
+    }
+
+    #[test]
+    fn test_windowed_score_with_no_history_equals_current_score() {
+        let window = SessionWindow::default();
+        assert_eq!(window.windowed_score(4, 0.5), 4);
+    }
+
+    #[test]
+    fn test_windowed_score_decays_prior_entries() {
+        let mut window = SessionWindow::default();
+        window.record(entry(4), 5);
+        // One prior entry, one step back: 4 * 0.5 = 2, plus current 3 => 5.
+        assert_eq!(window.windowed_score(3, 0.5), 5);
+    }
+
+    #[test]
+    fn test_windowed_score_accumulates_multiple_entries_catches_split_payload() {
+        let mut window = SessionWindow::default();
+        window.record(entry(3), 5);
+        window.record(entry(3), 5);
+        // steps_ago 0 (most recent prior): 3 * 0.5 = 1.5
+        // steps_ago 1 (older prior):        3 * 0.25 = 0.75
+        // current: 3 => total 3 + 1.5 + 0.75 = 5.25, rounds to 5
+        assert_eq!(window.windowed_score(3, 0.5), 5);
+    }
+
+    #[test]
+    fn test_record_evicts_oldest_beyond_window_size() {
+        let mut window = SessionWindow::default();
+        window.record(entry(10), 2);
+        window.record(entry(10), 2);
+        window.record(entry(10), 2);
+        assert_eq!(window.entries.len(), 2);
+    }
+
+    #[test]
+    fn test_decayed_category_scores_sums_across_entries() {
+        let mut window = SessionWindow::default();
+        window.record(entry(4), 5);
+        window.record(entry(2), 5);
+        let totals = window.decayed_category_scores(0.5);
+        assert_eq!(totals.len(), 1);
+        let (category, total) = &totals[0];
+        assert_eq!(category, "prompt_override");
+        // steps_ago 0 (the `2` entry, most recent prior): 2 * 0.5 = 1
+        // steps_ago 1 (the `4` entry): 4 * 0.25 = 1
+        assert_eq!(*total, 2);
+    }
+}
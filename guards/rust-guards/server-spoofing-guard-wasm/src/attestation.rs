@@ -0,0 +1,211 @@
+//! Cryptographic server attestation, replacing name-only whitelisting.
+//!
+//! `evaluate_server_connection` otherwise trusts `context.server_name`
+//! matched against a text whitelist — exactly what typosquatting defeats.
+//! When a whitelist entry carries a `trusted_issuer` JWK, a server can
+//! instead present a signed attestation token binding its identity to that
+//! key: a compact JWT-shaped
+//! `base64url(header).base64url(claims).base64url(sig)` whose claims are
+//! `{server_name, tool_fingerprints, iat, exp}`. A signature that verifies
+//! against the configured key, hasn't expired, and whose claimed identity
+//! matches what's observed proves the connection cryptographically,
+//! independent of how close the presented name is to an approved one.
+//!
+//! `context.attestation` is a `guard-context` field in the `security-guard`
+//! WIT world (see `wit/`), alongside `capability-token` which the `ucan`
+//! module reads the same way. Signature verification uses `ed25519_dalek`
+//! with `default-features = false` so it stays no-std-friendly inside the
+//! WASM component.
+
+use std::collections::HashMap;
+
+/// An Ed25519 public key in JWK OKP form, as configured on a whitelist entry.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct TrustedIssuerJwk {
+    pub kty: String,
+    pub crv: String,
+    /// Raw 32-byte Ed25519 public key, base64url-encoded (unpadded).
+    pub x: String,
+}
+
+/// Parse a `trusted_issuer` JWK object out of a whitelist entry's JSON.
+pub fn parse_trusted_issuer(val: &serde_json::Value) -> Option<TrustedIssuerJwk> {
+    serde_json::from_value(val.clone()).ok()
+}
+
+/// Claims carried by an attestation token.
+#[derive(Debug, Clone, serde::Deserialize)]
+struct AttestationClaims {
+    server_name: String,
+    #[serde(default)]
+    tool_fingerprints: HashMap<String, String>,
+    #[serde(default)]
+    #[allow(dead_code)]
+    iat: i64,
+    exp: i64,
+}
+
+/// Verify a server-presented attestation token against `issuer`'s key,
+/// checking the signature, expiry, and that the claimed identity matches
+/// what's observed on the connection.
+///
+/// `observed_tool_fingerprints` is whatever this guard has previously
+/// registered for the server (see `state::get_tool_registry`); `None` means
+/// no tools have been observed yet (e.g. a first-time connection), in which
+/// case the fingerprint claim is accepted on trust and re-checked once
+/// `evaluate_tools_list` actually registers tools.
+pub fn verify(
+    token: &str,
+    issuer: &TrustedIssuerJwk,
+    observed_server_name: &str,
+    observed_tool_fingerprints: Option<&HashMap<String, String>>,
+    now: i64,
+) -> Result<(), String> {
+    if issuer.kty != "OKP" || issuer.crv != "Ed25519" {
+        return Err(format!(
+            "unsupported trusted issuer key type '{}/{}'",
+            issuer.kty, issuer.crv
+        ));
+    }
+
+    let (claims, signing_input, sig) = decode_token(token)?;
+
+    let pubkey_bytes = base64url_decode(&issuer.x)?;
+    if !verify_signature(&pubkey_bytes, &signing_input, &sig) {
+        return Err("invalid signature".to_string());
+    }
+
+    if now >= claims.exp {
+        return Err("attestation expired".to_string());
+    }
+
+    if claims.server_name != observed_server_name {
+        return Err(format!(
+            "attested server_name '{}' does not match observed '{}'",
+            claims.server_name, observed_server_name
+        ));
+    }
+
+    if let Some(observed) = observed_tool_fingerprints {
+        if claims.tool_fingerprints != *observed {
+            return Err("attested tool_fingerprints do not match observed tools".to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// Split a compact attestation token into claims, signing input, and signature bytes.
+fn decode_token(token: &str) -> Result<(AttestationClaims, String, Vec<u8>), String> {
+    let mut parts = token.splitn(3, '.');
+    let header = parts.next().ok_or("missing header segment")?;
+    let payload = parts.next().ok_or("missing payload segment")?;
+    let sig = parts.next().ok_or("missing signature segment")?;
+
+    let payload_bytes = base64url_decode(payload)?;
+    let claims: AttestationClaims =
+        serde_json::from_slice(&payload_bytes).map_err(|e| format!("bad claims JSON: {e}"))?;
+    let sig_bytes = base64url_decode(sig)?;
+
+    Ok((claims, format!("{header}.{payload}"), sig_bytes))
+}
+
+fn verify_signature(pubkey_bytes: &[u8], signing_input: &str, sig: &[u8]) -> bool {
+    let Ok(key_array): Result<[u8; 32], _> = pubkey_bytes.try_into() else {
+        return false;
+    };
+    let Ok(verifying_key) = ed25519_dalek::VerifyingKey::from_bytes(&key_array) else {
+        return false;
+    };
+    let Ok(signature) = ed25519_dalek::Signature::from_slice(sig) else {
+        return false;
+    };
+    use ed25519_dalek::Verifier;
+    verifying_key
+        .verify(signing_input.as_bytes(), &signature)
+        .is_ok()
+}
+
+fn base64url_decode(s: &str) -> Result<Vec<u8>, String> {
+    use base64::Engine;
+    base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(s)
+        .map_err(|e| format!("bad base64url: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_trusted_issuer_valid() {
+        let val = serde_json::json!({
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"
+        });
+        let jwk = parse_trusted_issuer(&val).unwrap();
+        assert_eq!(jwk.kty, "OKP");
+        assert_eq!(jwk.crv, "Ed25519");
+    }
+
+    #[test]
+    fn test_parse_trusted_issuer_missing_field() {
+        let val = serde_json::json!({ "kty": "OKP" });
+        assert!(parse_trusted_issuer(&val).is_none());
+    }
+
+    #[test]
+    fn test_verify_rejects_unsupported_key_type() {
+        let issuer = TrustedIssuerJwk {
+            kty: "RSA".to_string(),
+            crv: "".to_string(),
+            x: "".to_string(),
+        };
+        let err = verify("a.b.c", &issuer, "server", None, 0).unwrap_err();
+        assert!(err.contains("unsupported"));
+    }
+
+    #[test]
+    fn test_verify_rejects_malformed_token() {
+        let issuer = TrustedIssuerJwk {
+            kty: "OKP".to_string(),
+            crv: "Ed25519".to_string(),
+            x: "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo".to_string(),
+        };
+        assert!(verify("not-a-jwt", &issuer, "server", None, 0).is_err());
+    }
+
+    #[test]
+    fn test_verify_signature_and_roundtrip() {
+        use base64::Engine;
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let verifying_key = signing_key.verifying_key();
+        let issuer = TrustedIssuerJwk {
+            kty: "OKP".to_string(),
+            crv: "Ed25519".to_string(),
+            x: base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .encode(verifying_key.to_bytes()),
+        };
+
+        let header = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(b"{}");
+        let claims_json = serde_json::json!({
+            "server_name": "finance-tools",
+            "tool_fingerprints": {},
+            "iat": 0,
+            "exp": 9_999_999_999i64,
+        })
+        .to_string();
+        let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(claims_json);
+        let signing_input = format!("{header}.{payload}");
+        let sig = signing_key.sign(signing_input.as_bytes());
+        let sig_b64 = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(sig.to_bytes());
+        let token = format!("{signing_input}.{sig_b64}");
+
+        assert!(verify(&token, &issuer, "finance-tools", Some(&HashMap::new()), 0).is_ok());
+        assert!(verify(&token, &issuer, "other-server", Some(&HashMap::new()), 0).is_err());
+        assert!(verify(&token, &issuer, "finance-tools", Some(&HashMap::new()), 10_000_000_000).is_err());
+    }
+}
@@ -0,0 +1,144 @@
+//! BK-tree index over whitelist names for sublinear fuzzy lookup.
+//!
+//! A BK-tree is a metric tree keyed by edit distance: each node stores
+//! children indexed by their integer Levenshtein distance to the parent.
+//! Querying for all entries within distance `k` of a word only needs to
+//! recurse into children whose edge distance lies in `[d-k, d+k]` (the
+//! triangle inequality prunes the rest), so lookups cost roughly
+//! `O(log n)` comparisons instead of scanning every whitelist entry.
+
+use crate::levenshtein::levenshtein_distance;
+use std::collections::HashMap;
+
+struct Node {
+    word: String,
+    children: HashMap<usize, Box<Node>>,
+}
+
+/// A BK-tree over a fixed set of words, built once and queried many times.
+pub struct BkTree {
+    root: Option<Box<Node>>,
+}
+
+impl BkTree {
+    pub fn new() -> Self {
+        BkTree { root: None }
+    }
+
+    /// Build a tree from `words` in one pass.
+    pub fn build<I: IntoIterator<Item = String>>(words: I) -> Self {
+        let mut tree = BkTree::new();
+        for word in words {
+            tree.insert(word);
+        }
+        tree
+    }
+
+    pub fn insert(&mut self, word: String) {
+        let Some(root) = &mut self.root else {
+            self.root = Some(Box::new(Node {
+                word,
+                children: HashMap::new(),
+            }));
+            return;
+        };
+
+        let mut node = root.as_mut();
+        loop {
+            let dist = levenshtein_distance(&node.word, &word);
+            if dist == 0 {
+                return; // already present
+            }
+            match node.children.get_mut(&dist) {
+                Some(child) => node = child,
+                None => {
+                    node.children.insert(
+                        dist,
+                        Box::new(Node {
+                            word,
+                            children: HashMap::new(),
+                        }),
+                    );
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Return every indexed word within edit distance `k` of `query`.
+    pub fn find_within(&self, query: &str, k: usize) -> Vec<&str> {
+        let mut results = Vec::new();
+        if let Some(root) = &self.root {
+            Self::search(root, query, k, &mut results);
+        }
+        results
+    }
+
+    fn search<'a>(node: &'a Node, query: &str, k: usize, out: &mut Vec<&'a str>) {
+        let dist = levenshtein_distance(&node.word, query);
+        if dist <= k {
+            out.push(&node.word);
+        }
+        let lower = dist.saturating_sub(k);
+        let upper = dist + k;
+        for (&edge, child) in &node.children {
+            if edge >= lower && edge <= upper {
+                Self::search(child, query, k, out);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+}
+
+/// Convert a similarity threshold in `[0,1]` into the max edit distance that
+/// still satisfies it for a string of length `max_len`.
+pub fn threshold_to_max_distance(threshold: f64, max_len: usize) -> usize {
+    ((1.0 - threshold) * max_len as f64).ceil() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_word_found_at_distance_zero() {
+        let tree = BkTree::build(["finance-tools".to_string(), "weather-api".to_string()]);
+        let results = tree.find_within("finance-tools", 0);
+        assert_eq!(results, vec!["finance-tools"]);
+    }
+
+    #[test]
+    fn test_finds_near_match_within_k() {
+        let tree = BkTree::build(["finance-tools".to_string()]);
+        let results = tree.find_within("finance-toals", 1);
+        assert_eq!(results, vec!["finance-tools"]);
+    }
+
+    #[test]
+    fn test_excludes_far_match() {
+        let tree = BkTree::build(["finance-tools".to_string()]);
+        let results = tree.find_within("weather-api", 1);
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_threshold_to_max_distance() {
+        assert_eq!(threshold_to_max_distance(0.85, 13), 2);
+        assert_eq!(threshold_to_max_distance(1.0, 13), 0);
+    }
+
+    #[test]
+    fn test_multiple_candidates_within_radius() {
+        let tree = BkTree::build([
+            "finance-tools".to_string(),
+            "finance-toals".to_string(),
+            "weather-api".to_string(),
+        ]);
+        let mut results = tree.find_within("finance-tools", 2);
+        results.sort();
+        assert_eq!(results, vec!["finance-toals", "finance-tools"]);
+    }
+}
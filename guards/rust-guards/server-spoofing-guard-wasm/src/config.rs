@@ -6,6 +6,27 @@ pub struct WhitelistEntry {
     pub name: String,
     pub url_pattern: Option<String>,
     pub tool_fingerprints: HashMap<String, String>,
+    /// UCAN ability (e.g. `mcp/invoke`) this server's tools require a valid
+    /// delegation chain to grant before `evaluate_tool_invoke` allows a call.
+    pub required_capability: Option<String>,
+    /// DID of this entry's resource owner, used as the expected root `iss`
+    /// when walking a presented UCAN's `prf` chain back to its root.
+    pub trusted_issuer_did: Option<String>,
+    /// Ed25519 public key (JWK) a server can prove possession of via a
+    /// signed `context.attestation` token, establishing identity
+    /// cryptographically instead of by name similarity. See `attestation`.
+    pub trusted_issuer: Option<crate::attestation::TrustedIssuerJwk>,
+    /// Minimum `(major, minor)` protocol version this server must negotiate.
+    /// Combined with the last-seen version recorded in `state`, a drop below
+    /// either bound flags a downgrade attack.
+    pub expected_protocol_version: Option<(u16, u16)>,
+    /// Capability set this server is pinned to. Once a connection has been
+    /// observed with this set, gaining or losing any of these capabilities
+    /// on a later connection is treated as capability drift.
+    pub expected_capabilities: Option<Vec<String>>,
+    /// CycloneDX-style component manifest pinning this server's tools by
+    /// package-URL and expected fingerprint hash. See `sbom`.
+    pub sbom_manifest: Vec<crate::sbom::SbomComponent>,
 }
 
 /// Parsed guard configuration with all 6 fields.
@@ -17,6 +38,13 @@ pub struct GuardConfig {
     pub typosquat_detection_enabled: bool,
     pub typosquat_similarity_threshold: f64,
     pub tool_mimicry_detection_enabled: bool,
+    /// When true, a server with no SBOM manifest (or whose manifest doesn't
+    /// cover an advertised tool) is denied rather than merely unchecked.
+    pub require_sbom: bool,
+    /// Operator-supplied confusable sequences (e.g. a newly observed
+    /// lookalike font rendering), applied on top of the built-in table in
+    /// `typosquat::confusable_skeleton_with` without requiring a redeploy.
+    pub custom_confusables: HashMap<String, String>,
 }
 
 impl Default for GuardConfig {
@@ -28,6 +56,8 @@ impl Default for GuardConfig {
             typosquat_detection_enabled: true,
             typosquat_similarity_threshold: 0.85,
             tool_mimicry_detection_enabled: true,
+            require_sbom: false,
+            custom_confusables: HashMap::new(),
         }
     }
 }
@@ -40,6 +70,8 @@ const CONFIG_KEYS: &[&str] = &[
     "typosquat_detection_enabled",
     "typosquat_similarity_threshold",
     "tool_mimicry_detection_enabled",
+    "require_sbom",
+    "custom_confusables",
 ];
 
 /// Load configuration from host.
@@ -112,6 +144,21 @@ fn parse_config(val: &serde_json::Value) -> GuardConfig {
         .and_then(|v| v.as_bool())
         .unwrap_or(true);
 
+    let require_sbom = val
+        .get("require_sbom")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let custom_confusables = val
+        .get("custom_confusables")
+        .and_then(|v| v.as_object())
+        .map(|obj| {
+            obj.iter()
+                .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+
     let whitelist = val
         .get("whitelist")
         .and_then(|v| v.as_array())
@@ -132,10 +179,44 @@ fn parse_config(val: &serde_json::Value) -> GuardConfig {
                                 .collect()
                         })
                         .unwrap_or_default();
+                    let required_capability = entry
+                        .get("required_capability")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string());
+                    let trusted_issuer_did = entry
+                        .get("trusted_issuer_did")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string());
+                    let trusted_issuer = entry
+                        .get("trusted_issuer")
+                        .and_then(crate::attestation::parse_trusted_issuer);
+                    let expected_protocol_version = entry
+                        .get("expected_protocol_version")
+                        .and_then(|v| v.as_array())
+                        .filter(|arr| arr.len() == 2)
+                        .and_then(|arr| Some((arr[0].as_u64()? as u16, arr[1].as_u64()? as u16)));
+                    let expected_capabilities = entry
+                        .get("expected_capabilities")
+                        .and_then(|v| v.as_array())
+                        .map(|arr| {
+                            arr.iter()
+                                .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                                .collect()
+                        });
+                    let sbom_manifest = entry
+                        .get("sbom_manifest")
+                        .map(crate::sbom::parse_manifest)
+                        .unwrap_or_default();
                     Some(WhitelistEntry {
                         name,
                         url_pattern,
                         tool_fingerprints,
+                        required_capability,
+                        trusted_issuer_did,
+                        trusted_issuer,
+                        expected_protocol_version,
+                        expected_capabilities,
+                        sbom_manifest,
                     })
                 })
                 .collect()
@@ -149,9 +230,16 @@ fn parse_config(val: &serde_json::Value) -> GuardConfig {
         typosquat_detection_enabled,
         typosquat_similarity_threshold,
         tool_mimicry_detection_enabled,
+        require_sbom,
+        custom_confusables,
     }
 }
 
+/// Get operator-supplied confusable sequences from config.
+pub fn get_custom_confusables() -> HashMap<String, String> {
+    get_config().custom_confusables
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,210 @@
+//! Property-based / differential fuzzing harness for the detection engine.
+//!
+//! Gated behind the `dev` feature so none of this, or its `fake`/`rand`
+//! dependencies, ships in the guard's production WASM component. Generates
+//! synthetic whitelist entries, server names and `Tool` lists via `fake`'s
+//! `Dummy` derive, mutates an approved name into a typosquat sibling, and
+//! drives `typosquat::evaluate_candidate`, `mimicry::check_tool_mimicry_against`
+//! and `levenshtein::levenshtein_ratio` to catch regressions the handful of
+//! hand-traced unit tests miss.
+
+#![cfg(any(test, feature = "dev"))]
+
+use crate::config::WhitelistEntry;
+use crate::exports::mcp::security_guard::guard::Tool;
+use crate::levenshtein::levenshtein_ratio;
+use crate::mimicry::check_tool_mimicry_against;
+use crate::typosquat::{confusable_skeleton, evaluate_candidate, scripts_in};
+use fake::{Dummy, Fake};
+use rand::Rng;
+
+const NAME_POOL: &[&str] = &[
+    "finance-tools",
+    "weather-api",
+    "company-tools",
+    "payments-gateway",
+    "data-warehouse",
+];
+
+/// One of the mutation classes from the request: single-char substitution,
+/// homoglyph swap, adjacent-character transposition, or an inserted junk char.
+#[derive(Debug, Clone, Copy, Dummy)]
+enum Mutation {
+    SingleSubstitution,
+    HomoglyphSwap,
+    Transposition,
+    InsertedChar,
+}
+
+/// An approved name paired with a mutated sibling that the detector should
+/// flag, and an unrelated name it must not.
+#[derive(Debug)]
+struct FuzzCase {
+    approved: String,
+    mutated_sibling: String,
+    unrelated: String,
+}
+
+impl Dummy<fake::Faker> for FuzzCase {
+    fn dummy_with_rng<R: Rng + ?Sized>(config: &fake::Faker, rng: &mut R) -> Self {
+        let pool_idx = rng.gen_range(0..NAME_POOL.len());
+        let approved = NAME_POOL[pool_idx].to_string();
+        let mutation: Mutation = config.fake_with_rng(rng);
+        let mutated_sibling = apply_mutation(&approved, mutation, rng);
+        let unrelated = NAME_POOL[(pool_idx + 1) % NAME_POOL.len()].to_string();
+        FuzzCase {
+            approved,
+            mutated_sibling,
+            unrelated,
+        }
+    }
+}
+
+fn apply_mutation<R: Rng + ?Sized>(name: &str, mutation: Mutation, rng: &mut R) -> String {
+    let mut chars: Vec<char> = name.chars().collect();
+    if chars.is_empty() {
+        return name.to_string();
+    }
+    let idx = rng.gen_range(0..chars.len());
+
+    match mutation {
+        Mutation::SingleSubstitution => {
+            chars[idx] = if chars[idx] == 'o' { '0' } else { 'x' };
+        }
+        Mutation::HomoglyphSwap => {
+            // Swap a Latin vowel for its Cyrillic lookalike.
+            chars[idx] = match chars[idx] {
+                'a' => '\u{0430}',
+                'e' => '\u{0435}',
+                'o' => '\u{043E}',
+                'p' => '\u{0440}',
+                other => other,
+            };
+        }
+        Mutation::Transposition => {
+            if idx + 1 < chars.len() {
+                chars.swap(idx, idx + 1);
+            }
+        }
+        Mutation::InsertedChar => {
+            chars.insert(idx, 'z');
+        }
+    }
+    chars.into_iter().collect()
+}
+
+fn whitelist_of(name: &str) -> Vec<WhitelistEntry> {
+    vec![WhitelistEntry {
+        name: name.to_string(),
+        url_pattern: None,
+        tool_fingerprints: Default::default(),
+        required_capability: None,
+        trusted_issuer_did: None,
+        trusted_issuer: None,
+        expected_protocol_version: None,
+        expected_capabilities: None,
+        sbom_manifest: Vec::new(),
+    }]
+}
+
+fn check_against(approved: &str, suspect: &str, threshold: f64) -> bool {
+    let test_name = suspect.to_lowercase();
+    let test_skeleton = confusable_skeleton(&test_name);
+    let test_scripts = scripts_in(suspect);
+    let entry = &whitelist_of(approved)[0];
+    let no_custom = std::collections::HashMap::new();
+    evaluate_candidate(
+        entry,
+        &test_name,
+        &test_skeleton,
+        &test_scripts,
+        threshold,
+        &no_custom,
+    )
+    .is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fake::Faker;
+
+    const ROUNDS: usize = 200;
+
+    #[test]
+    fn test_recall_every_mutation_within_threshold_is_flagged() {
+        for _ in 0..ROUNDS {
+            let case: FuzzCase = Faker.fake();
+            if case.mutated_sibling == case.approved {
+                continue; // mutation happened to be a no-op; not a useful case
+            }
+            assert!(
+                check_against(&case.approved, &case.mutated_sibling, 0.80),
+                "failed to flag mutated sibling '{}' of '{}'",
+                case.mutated_sibling,
+                case.approved
+            );
+        }
+    }
+
+    #[test]
+    fn test_unrelated_names_are_never_flagged() {
+        for _ in 0..ROUNDS {
+            let case: FuzzCase = Faker.fake();
+            assert!(
+                !check_against(&case.approved, &case.unrelated, 0.80),
+                "incorrectly flagged unrelated name '{}' against '{}'",
+                case.unrelated,
+                case.approved
+            );
+        }
+    }
+
+    #[test]
+    fn test_levenshtein_ratio_is_symmetric_and_bounded() {
+        for _ in 0..ROUNDS {
+            let case: FuzzCase = Faker.fake();
+            let r1 = levenshtein_ratio(&case.approved, &case.mutated_sibling);
+            let r2 = levenshtein_ratio(&case.mutated_sibling, &case.approved);
+            assert!((r1 - r2).abs() < f64::EPSILON);
+            assert!((0.0..=1.0).contains(&r1));
+        }
+    }
+
+    #[test]
+    fn test_detect_typosquat_never_matches_exact_whitelist_member() {
+        for name in NAME_POOL {
+            assert!(!check_against(name, name, 0.80));
+        }
+    }
+
+    /// Pins the two mutation shapes `test_recall_every_mutation_within_threshold_is_flagged`
+    /// covers but that fit neither `SkeletonMatch` (no confusable folding
+    /// applies) nor `SingleCharSubstitution` (an adjacent swap differs at two
+    /// positions; an insertion changes the length) — both still need to
+    /// clear the recall test's threshold via the general edit-distance path.
+    #[test]
+    fn test_transposition_and_insertion_are_flagged() {
+        // Adjacent transposition: "finance-tools" -> "ifnance-tools".
+        assert!(check_against("finance-tools", "ifnance-tools", 0.80));
+        // Inserted character: "finance-tools" -> "finanzce-tools".
+        assert!(check_against("finance-tools", "finanzce-tools", 0.80));
+    }
+
+    #[test]
+    fn test_mimicry_fingerprint_match_is_detected() {
+        let tool = Tool {
+            name: "transfer".to_string(),
+            description: Some("Moves funds".to_string()),
+            input_schema: "{}".to_string(),
+        };
+        let fp = crate::fingerprint::compute_tool_fingerprint(&tool);
+
+        let mut entry = whitelist_of("finance-tools").remove(0);
+        entry.tool_fingerprints.insert("transfer".to_string(), fp);
+        let whitelist = vec![entry];
+
+        let result = check_tool_mimicry_against("evil-clone", &[tool], &whitelist);
+        assert!(result.is_some());
+    }
+}
@@ -0,0 +1,166 @@
+//! Protocol-version and capability pinning.
+//!
+//! A spoofed or compromised server can present a trusted name (or even a
+//! valid attestation) while quietly negotiating a weaker protocol surface —
+//! an older protocol version missing security fixes, or a different
+//! capability set than the one an operator reviewed and approved. This
+//! compares each connection's negotiated `(protocol_version, capabilities)`
+//! against what's pinned in the whitelist entry and what was last observed
+//! for that server (see `state::get_last_handshake`), independent of how
+//! identity itself was established.
+
+use crate::config::WhitelistEntry;
+use crate::state::ServerHandshake;
+
+/// A pinning violation to surface as a `Decision::Deny`.
+pub struct HandshakeViolation {
+    pub code: &'static str,
+    pub message: String,
+    pub details: serde_json::Value,
+}
+
+/// Check a server's newly negotiated protocol version and capabilities
+/// against its pinned expectations (`entry`) and its last-seen handshake
+/// (`last_seen`), if any.
+///
+/// Returns `Err` with the first violation found (downgrade is checked
+/// before drift). Returns `Ok(())` when nothing is pinned yet, or when the
+/// new handshake is consistent with both the pin and the last-seen values.
+pub fn check_drift(
+    server_name: &str,
+    entry: Option<&WhitelistEntry>,
+    last_seen: Option<&ServerHandshake>,
+    protocol_version: (u16, u16),
+    capabilities: &[String],
+) -> Result<(), HandshakeViolation> {
+    let min_version = entry.and_then(|e| e.expected_protocol_version);
+    let last_seen_version = last_seen.map(|h| h.protocol_version);
+
+    let floor = match (min_version, last_seen_version) {
+        (Some(min), Some(seen)) => Some(min.max(seen)),
+        (Some(min), None) => Some(min),
+        (None, Some(seen)) => Some(seen),
+        (None, None) => None,
+    };
+    if let Some(floor) = floor {
+        if protocol_version < floor {
+            return Err(HandshakeViolation {
+                code: "protocol_downgrade",
+                message: format!(
+                    "Server '{}' negotiated protocol version {}.{} below the expected floor {}.{}",
+                    server_name, protocol_version.0, protocol_version.1, floor.0, floor.1
+                ),
+                details: serde_json::json!({
+                    "server_name": server_name,
+                    "negotiated_version": [protocol_version.0, protocol_version.1],
+                    "expected_floor": [floor.0, floor.1],
+                }),
+            });
+        }
+    }
+
+    if let Some(pinned) = entry.and_then(|e| e.expected_capabilities.as_ref()) {
+        let observed: std::collections::HashSet<&str> =
+            capabilities.iter().map(|s| s.as_str()).collect();
+        let expected: std::collections::HashSet<&str> =
+            pinned.iter().map(|s| s.as_str()).collect();
+        if observed != expected {
+            let added: Vec<&str> = observed.difference(&expected).copied().collect();
+            let dropped: Vec<&str> = expected.difference(&observed).copied().collect();
+            return Err(HandshakeViolation {
+                code: "capability_drift",
+                message: format!(
+                    "Server '{}' capabilities no longer match the pinned set",
+                    server_name
+                ),
+                details: serde_json::json!({
+                    "server_name": server_name,
+                    "added": added,
+                    "dropped": dropped,
+                }),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn entry_with(
+        expected_protocol_version: Option<(u16, u16)>,
+        expected_capabilities: Option<Vec<String>>,
+    ) -> WhitelistEntry {
+        WhitelistEntry {
+            name: "trusted".to_string(),
+            url_pattern: None,
+            tool_fingerprints: HashMap::new(),
+            required_capability: None,
+            trusted_issuer_did: None,
+            trusted_issuer: None,
+            expected_protocol_version,
+            expected_capabilities,
+            sbom_manifest: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_no_pins_allows_anything() {
+        let entry = entry_with(None, None);
+        assert!(check_drift("trusted", Some(&entry), None, (1, 0), &[]).is_ok());
+    }
+
+    #[test]
+    fn test_protocol_downgrade_vs_pinned_minimum() {
+        let entry = entry_with(Some((2, 0)), None);
+        let err = check_drift("trusted", Some(&entry), None, (1, 5), &[]).unwrap_err();
+        assert_eq!(err.code, "protocol_downgrade");
+    }
+
+    #[test]
+    fn test_protocol_downgrade_vs_last_seen() {
+        let last_seen = ServerHandshake {
+            protocol_version: (2, 1),
+            capabilities: vec![],
+        };
+        let err = check_drift("trusted", None, Some(&last_seen), (2, 0), &[]).unwrap_err();
+        assert_eq!(err.code, "protocol_downgrade");
+    }
+
+    #[test]
+    fn test_equal_or_higher_version_passes() {
+        let entry = entry_with(Some((2, 0)), None);
+        let last_seen = ServerHandshake {
+            protocol_version: (2, 0),
+            capabilities: vec![],
+        };
+        assert!(check_drift("trusted", Some(&entry), Some(&last_seen), (2, 0), &[]).is_ok());
+        assert!(check_drift("trusted", Some(&entry), Some(&last_seen), (3, 0), &[]).is_ok());
+    }
+
+    #[test]
+    fn test_capability_drift_on_dropped_capability() {
+        let entry = entry_with(None, Some(vec!["streaming".to_string(), "tools".to_string()]));
+        let err =
+            check_drift("trusted", Some(&entry), None, (1, 0), &["tools".to_string()]).unwrap_err();
+        assert_eq!(err.code, "capability_drift");
+    }
+
+    #[test]
+    fn test_capability_drift_on_added_capability() {
+        let entry = entry_with(None, Some(vec!["tools".to_string()]));
+        let observed = vec!["tools".to_string(), "sampling".to_string()];
+        let err = check_drift("trusted", Some(&entry), None, (1, 0), &observed).unwrap_err();
+        assert_eq!(err.code, "capability_drift");
+    }
+
+    #[test]
+    fn test_matching_capabilities_pass() {
+        let entry = entry_with(None, Some(vec!["tools".to_string(), "streaming".to_string()]));
+        let observed = vec!["streaming".to_string(), "tools".to_string()];
+        assert!(check_drift("trusted", Some(&entry), None, (1, 0), &observed).is_ok());
+    }
+}
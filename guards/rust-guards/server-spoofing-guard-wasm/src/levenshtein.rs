@@ -1,13 +1,15 @@
-/// Calculate Levenshtein similarity ratio between two strings.
-/// Returns a value between 0.0 (completely different) and 1.0 (identical).
-///
-/// Direct port of the Python `levenshtein_ratio` function.
-pub fn levenshtein_ratio(s1: &str, s2: &str) -> f64 {
-    if s1.is_empty() || s2.is_empty() {
-        return 0.0;
-    }
+/// Calculate the Levenshtein edit distance between two strings (number of
+/// single-character insertions/deletions/substitutions to turn one into the
+/// other).
+pub fn levenshtein_distance(s1: &str, s2: &str) -> usize {
     if s1 == s2 {
-        return 1.0;
+        return 0;
+    }
+    if s1.is_empty() {
+        return s2.chars().count();
+    }
+    if s2.is_empty() {
+        return s1.chars().count();
     }
 
     let chars1: Vec<char> = s1.chars().collect();
@@ -20,7 +22,6 @@ pub fn levenshtein_ratio(s1: &str, s2: &str) -> f64 {
         (chars1, chars2)
     };
 
-    let len1 = chars1.len();
     let len2 = chars2.len();
 
     let mut distances: Vec<usize> = (0..=len2).collect();
@@ -40,7 +41,24 @@ pub fn levenshtein_ratio(s1: &str, s2: &str) -> f64 {
         distances = new_distances;
     }
 
-    let distance = *distances.last().unwrap();
+    *distances.last().unwrap()
+}
+
+/// Calculate Levenshtein similarity ratio between two strings.
+/// Returns a value between 0.0 (completely different) and 1.0 (identical).
+///
+/// Direct port of the Python `levenshtein_ratio` function.
+pub fn levenshtein_ratio(s1: &str, s2: &str) -> f64 {
+    if s1.is_empty() || s2.is_empty() {
+        return 0.0;
+    }
+    if s1 == s2 {
+        return 1.0;
+    }
+
+    let len1 = s1.chars().count();
+    let len2 = s2.chars().count();
+    let distance = levenshtein_distance(s1, s2);
     1.0 - (distance as f64 / len1.max(len2) as f64)
 }
 
@@ -78,6 +96,21 @@ mod tests {
         assert!(ratio < 0.85, "Expected < 0.85, got {}", ratio);
     }
 
+    #[test]
+    fn test_distance_identical_strings() {
+        assert_eq!(levenshtein_distance("hello", "hello"), 0);
+    }
+
+    #[test]
+    fn test_distance_single_substitution() {
+        assert_eq!(levenshtein_distance("finance-tools", "finance-toals"), 1);
+    }
+
+    #[test]
+    fn test_distance_empty_string() {
+        assert_eq!(levenshtein_distance("", "hello"), 5);
+    }
+
     #[test]
     fn test_symmetric() {
         let r1 = levenshtein_ratio("abc", "abd");
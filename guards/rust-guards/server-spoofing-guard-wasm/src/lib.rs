@@ -5,14 +5,38 @@
 //! 2. Typosquatting attacks (e.g., "company-to0ls" vs "company-tools")
 //! 3. Tool mimicry (malicious server copying trusted server's tools)
 //! 4. Tool namespace collisions across servers
+//! 5. Unauthorized tool invocation (UCAN capability delegation, see `ucan`)
+//! 6. Supply-chain tool drift (SBOM-backed provenance verification, see `sbom`)
+//!
+//! Capability gating uses `capability-token` and `gateway-did`/`now` clock
+//! from `guard-context` in the `security-guard` WIT world (see `wit/`) to
+//! validate a presented UCAN delegation chain (see `ucan`).
+//!
+//! Connection-time identity can additionally be established via
+//! `guard-context`'s `attestation` field: a signed token a server presents
+//! to prove it controls a whitelist entry's `trusted_issuer` key, rather
+//! than merely presenting a name that resembles one (see `attestation`).
+//!
+//! `guard-context` also carries `protocol_version: (u16, u16)` and
+//! `capabilities: Vec<String>` reported at handshake, so a server that
+//! passes identity checks but then negotiates a weaker protocol surface
+//! than it previously did (or than an operator pinned) can still be caught
+//! (see `handshake`).
 
+mod attestation;
+mod bktree;
 mod config;
 mod fingerprint;
+#[cfg(any(test, feature = "dev"))]
+mod fuzzing;
+mod handshake;
 mod levenshtein;
 mod mimicry;
+mod sbom;
 mod schema;
 mod state;
 mod typosquat;
+mod ucan;
 
 struct ServerSpoofingGuard;
 
@@ -38,30 +62,91 @@ impl Guest for ServerSpoofingGuard {
             return Ok(Decision::Allow);
         }
 
+        // A signed attestation binds this connection to a trusted issuer key
+        // cryptographically, so it's checked independently of (not gated by)
+        // name-based whitelist lookup: pre-filtering whitelist entries by the
+        // observed server name would reintroduce the exact spoofing vector
+        // attestation exists to close.
+        if let Some(token) = &context.attestation {
+            let observed_fingerprints =
+                state::get_tool_registry(|reg| reg.get(server_name).cloned());
+            let mut last_error = None;
+            for (entry, issuer) in cfg
+                .whitelist
+                .iter()
+                .filter_map(|e| e.trusted_issuer.as_ref().map(|issuer| (e, issuer)))
+            {
+                match attestation::verify(
+                    token,
+                    issuer,
+                    server_name,
+                    observed_fingerprints.as_ref(),
+                    context.now,
+                ) {
+                    Ok(()) => {
+                        log_debug(&format!(
+                            "Server '{}' authenticated via attestation bound to '{}'",
+                            server_name, entry.name
+                        ));
+                        return Ok(finalize_trusted_connection(server_name, Some(entry), &context));
+                    }
+                    Err(reason) => last_error = Some(reason),
+                }
+            }
+            if let Some(reason) = last_error {
+                log_warn(&format!(
+                    "Attestation presented by '{}' failed verification: {}",
+                    server_name, reason
+                ));
+                return Ok(Decision::Deny(DenyReason {
+                    code: "attestation_invalid".to_string(),
+                    message: format!(
+                        "Server '{}' presented an attestation that failed verification",
+                        server_name
+                    ),
+                    details: Some(
+                        serde_json::json!({
+                            "server_name": server_name,
+                            "reason": reason,
+                        })
+                        .to_string(),
+                    ),
+                }));
+            }
+        }
+
         // Check whitelist
-        if config::is_whitelisted(server_name) {
+        if let Some(entry) = cfg
+            .whitelist
+            .iter()
+            .find(|e| e.name.to_lowercase() == server_name.to_lowercase())
+        {
             log_debug(&format!("Server '{}' is whitelisted", server_name));
-            return Ok(Decision::Allow);
+            return Ok(finalize_trusted_connection(server_name, Some(entry), &context));
         }
 
         // Check for typosquat
         if cfg.typosquat_detection_enabled {
             if let Some(typosquat_match) = typosquat::detect_typosquat(server_name) {
                 log_warn(&format!(
-                    "Typosquat detected: '{}' similar to '{}'",
-                    server_name, typosquat_match
+                    "Typosquat detected: '{}' similar to '{}' (via {})",
+                    server_name,
+                    typosquat_match.approved_name,
+                    typosquat_match.detection_path.as_str()
                 ));
                 return Ok(Decision::Deny(DenyReason {
                     code: "typosquat_detected".to_string(),
                     message: format!(
                         "Server '{}' appears to be typosquatting approved server '{}'",
-                        server_name, typosquat_match
+                        server_name, typosquat_match.approved_name
                     ),
                     details: Some(
                         serde_json::json!({
                             "detected_name": server_name,
-                            "similar_to": typosquat_match,
+                            "similar_to": typosquat_match.approved_name,
                             "attack_type": "typosquatting",
+                            "detection_path": typosquat_match.detection_path.as_str(),
+                            "matched_skeleton": typosquat_match.matched_skeleton,
                         })
                         .to_string(),
                     ),
@@ -150,6 +235,29 @@ impl Guest for ServerSpoofingGuard {
             }));
         }
 
+        // Check tool provenance against the server's SBOM manifest, if any
+        let whitelist = config::get_whitelist();
+        let manifest: &[sbom::SbomComponent] = whitelist
+            .iter()
+            .find(|e| e.name.to_lowercase() == server_name.to_lowercase())
+            .map(|e| e.sbom_manifest.as_slice())
+            .unwrap_or(&[]);
+        if cfg.require_sbom || !manifest.is_empty() {
+            if let Some(violation) =
+                sbom::check_provenance_against(server_name, &tools, manifest).into_iter().next()
+            {
+                log_warn(&format!(
+                    "SBOM provenance violation for '{}': {}",
+                    server_name, violation.message
+                ));
+                return Ok(Decision::Deny(DenyReason {
+                    code: violation.code.to_string(),
+                    message: violation.message,
+                    details: Some(violation.details.to_string()),
+                }));
+            }
+        }
+
         // Register tools for this server
         let tool_fingerprints: std::collections::HashMap<String, String> = tools
             .iter()
@@ -172,11 +280,66 @@ impl Guest for ServerSpoofingGuard {
     }
 
     fn evaluate_tool_invoke(
-        _tool_name: String,
+        tool_name: String,
         _arguments: String,
-        _context: GuardContext,
+        context: GuardContext,
     ) -> Result<Decision, String> {
-        Ok(Decision::Allow)
+        let server_name = &context.server_name;
+        let whitelist = config::get_whitelist();
+
+        let Some(entry) = whitelist
+            .iter()
+            .find(|e| e.name.to_lowercase() == server_name.to_lowercase())
+        else {
+            // Unknown servers are handled by evaluate_server_connection; nothing
+            // to capability-gate here.
+            return Ok(Decision::Allow);
+        };
+
+        let Some(required) = &entry.required_capability else {
+            return Ok(Decision::Allow);
+        };
+
+        let resource = format!("mcp://{}/{}", server_name, tool_name);
+        let token = context.capability_token.clone().unwrap_or_default();
+
+        match ucan::authorize(
+            &token,
+            &resource,
+            required,
+            &context.gateway_did,
+            context.now,
+            entry.trusted_issuer_did.as_deref(),
+        ) {
+            Ok(auth) => {
+                log_debug(&format!(
+                    "UCAN authorized '{}' on '{}' rooted at '{}'",
+                    required, resource, auth.root_issuer
+                ));
+                Ok(Decision::Allow)
+            }
+            Err(reason) => {
+                log_warn(&format!(
+                    "UCAN authorization denied for '{}' on '{}': {}",
+                    required, resource, reason
+                ));
+                Ok(Decision::Deny(DenyReason {
+                    code: "capability_denied".to_string(),
+                    message: format!(
+                        "Tool '{}' on server '{}' requires capability '{}' which the presented token does not grant",
+                        tool_name, server_name, required
+                    ),
+                    details: Some(
+                        serde_json::json!({
+                            "resource": resource,
+                            "required_capability": required,
+                            "reason": reason,
+                        })
+                        .to_string(),
+                    ),
+                }))
+            }
+        }
     }
 
     fn evaluate_response(
@@ -195,6 +358,49 @@ impl Guest for ServerSpoofingGuard {
     }
 }
 
+/// Run the protocol-version/capability drift check for a server that's
+/// already passed identity checks (whitelist or attestation), then record
+/// this handshake as the new last-seen baseline for future connections.
+fn finalize_trusted_connection(
+    server_name: &str,
+    entry: Option<&config::WhitelistEntry>,
+    context: &GuardContext,
+) -> Decision {
+    let last_seen = state::get_last_handshake(server_name);
+    match handshake::check_drift(
+        server_name,
+        entry,
+        last_seen.as_ref(),
+        context.protocol_version,
+        &context.capabilities,
+    ) {
+        Ok(()) => {
+            // Only a clean handshake becomes the new baseline; recording a
+            // violating one would let the next connection re-downgrade
+            // undetected against its own bad value.
+            state::record_handshake(
+                server_name,
+                state::ServerHandshake {
+                    protocol_version: context.protocol_version,
+                    capabilities: context.capabilities.clone(),
+                },
+            );
+            Decision::Allow
+        }
+        Err(violation) => {
+            log_warn(&format!(
+                "Handshake pinning violation for '{}': {}",
+                server_name, violation.message
+            ));
+            Decision::Deny(DenyReason {
+                code: violation.code.to_string(),
+                message: violation.message,
+                details: Some(violation.details.to_string()),
+            })
+        }
+    }
+}
+
 // Logging helpers using host functions
 #[allow(dead_code)]
 fn log_debug(msg: &str) {
@@ -10,10 +10,20 @@ use crate::exports::mcp::security_guard::guard::Tool;
 /// Matches Python `_check_tool_mimicry` from `app.py`.
 pub fn check_tool_mimicry(server_name: &str, tools: &[Tool]) -> Option<serde_json::Value> {
     let whitelist = config::get_whitelist();
+    check_tool_mimicry_against(server_name, tools, &whitelist)
+}
+
+/// Core of `check_tool_mimicry`, taking the whitelist explicitly so it can be
+/// driven directly by tests/the fuzzing harness without host config.
+pub(crate) fn check_tool_mimicry_against(
+    server_name: &str,
+    tools: &[Tool],
+    whitelist: &[config::WhitelistEntry],
+) -> Option<serde_json::Value> {
     let server_lower = server_name.to_lowercase();
 
     for tool in tools {
-        for entry in &whitelist {
+        for entry in whitelist {
             let entry_lower = entry.name.to_lowercase();
             if entry_lower == server_lower {
                 continue;
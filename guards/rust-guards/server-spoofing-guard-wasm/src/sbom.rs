@@ -0,0 +1,209 @@
+//! SBOM-backed tool provenance verification.
+//!
+//! Fingerprint-based mimicry detection (see `mimicry`) only catches a tool
+//! that copies another *trusted* server's tool; it says nothing about
+//! whether a server's own tools are the ones it's supposed to be exposing.
+//! A whitelist entry can instead carry a CycloneDX-style component manifest
+//! — each tool identified by a package-URL (`pkg:mcp/<server>/<tool>[@<version>]`)
+//! plus its expected fingerprint hash. `evaluate_tools_list` then requires
+//! every advertised tool to have a matching, hash-correct manifest entry,
+//! turning the guard into a supply-chain integrity check rather than just a
+//! cross-server comparison.
+
+use crate::exports::mcp::security_guard::guard::Tool;
+use crate::fingerprint;
+
+/// A single CycloneDX-style component entry: a package-URL and the hash
+/// its tool is expected to fingerprint to.
+#[derive(Debug, Clone)]
+pub struct SbomComponent {
+    pub purl: String,
+    pub hash: String,
+}
+
+/// Parse a `{"components": [{"purl": ..., "hash": ...}, ...]}` manifest,
+/// skipping individual malformed entries rather than rejecting the whole
+/// document.
+pub fn parse_manifest(val: &serde_json::Value) -> Vec<SbomComponent> {
+    val.get("components")
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|item| {
+                    let purl = item.get("purl")?.as_str()?.to_string();
+                    let hash = item.get("hash")?.as_str()?.to_string();
+                    Some(SbomComponent { purl, hash })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// A provenance check failure for a single tool.
+pub struct ProvenanceViolation {
+    pub code: &'static str,
+    pub message: String,
+    pub details: serde_json::Value,
+}
+
+/// Whether `purl` identifies `server_name`'s `tool_name`, ignoring any
+/// `@<version>` suffix.
+fn purl_matches(purl: &str, server_name: &str, tool_name: &str) -> bool {
+    let expected_prefix = format!("pkg:mcp/{}/{}", server_name, tool_name);
+    match purl.strip_prefix(&expected_prefix) {
+        Some("") => true,
+        Some(rest) => rest.starts_with('@'),
+        None => false,
+    }
+}
+
+/// Check every advertised tool against `manifest`, returning a violation
+/// for each tool missing from the manifest (`tool_not_in_sbom`) or present
+/// but fingerprinting to a different hash than pinned (`tool_hash_mismatch`).
+pub fn check_provenance_against(
+    server_name: &str,
+    tools: &[Tool],
+    manifest: &[SbomComponent],
+) -> Vec<ProvenanceViolation> {
+    let mut violations = Vec::new();
+
+    for tool in tools {
+        let Some(component) = manifest
+            .iter()
+            .find(|c| purl_matches(&c.purl, server_name, &tool.name))
+        else {
+            violations.push(ProvenanceViolation {
+                code: "tool_not_in_sbom",
+                message: format!(
+                    "Tool '{}' from server '{}' has no matching component in the SBOM manifest",
+                    tool.name, server_name
+                ),
+                details: serde_json::json!({
+                    "server_name": server_name,
+                    "tool_name": tool.name,
+                }),
+            });
+            continue;
+        };
+
+        let fp = fingerprint::compute_tool_fingerprint(tool);
+        if fp != component.hash {
+            violations.push(ProvenanceViolation {
+                code: "tool_hash_mismatch",
+                message: format!(
+                    "Tool '{}' from server '{}' no longer matches its pinned SBOM hash",
+                    tool.name, server_name
+                ),
+                details: serde_json::json!({
+                    "server_name": server_name,
+                    "tool_name": tool.name,
+                    "purl": component.purl,
+                    "expected_hash": component.hash,
+                    "actual_hash": fp,
+                }),
+            });
+        }
+    }
+
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_tool(name: &str, description: Option<&str>, schema: &str) -> Tool {
+        Tool {
+            name: name.to_string(),
+            description: description.map(|s| s.to_string()),
+            input_schema: schema.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_manifest_valid() {
+        let val = serde_json::json!({
+            "components": [
+                { "purl": "pkg:mcp/finance-tools/lookup@1.0.0", "hash": "abc123" }
+            ]
+        });
+        let manifest = parse_manifest(&val);
+        assert_eq!(manifest.len(), 1);
+        assert_eq!(manifest[0].purl, "pkg:mcp/finance-tools/lookup@1.0.0");
+    }
+
+    #[test]
+    fn test_parse_manifest_skips_malformed_component() {
+        let val = serde_json::json!({
+            "components": [
+                { "purl": "pkg:mcp/finance-tools/lookup@1.0.0" },
+                { "purl": "pkg:mcp/finance-tools/other@1.0.0", "hash": "def456" }
+            ]
+        });
+        let manifest = parse_manifest(&val);
+        assert_eq!(manifest.len(), 1);
+        assert_eq!(manifest[0].hash, "def456");
+    }
+
+    #[test]
+    fn test_parse_manifest_missing_components_key() {
+        let val = serde_json::json!({});
+        assert!(parse_manifest(&val).is_empty());
+    }
+
+    #[test]
+    fn test_tool_not_in_sbom() {
+        let tool = make_tool("lookup", Some("desc"), "{}");
+        let violations = check_provenance_against("finance-tools", &[tool], &[]);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].code, "tool_not_in_sbom");
+    }
+
+    #[test]
+    fn test_tool_hash_mismatch() {
+        let tool = make_tool("lookup", Some("desc"), "{}");
+        let manifest = vec![SbomComponent {
+            purl: "pkg:mcp/finance-tools/lookup@1.0.0".to_string(),
+            hash: "not-the-real-hash".to_string(),
+        }];
+        let violations = check_provenance_against("finance-tools", &[tool], &manifest);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].code, "tool_hash_mismatch");
+    }
+
+    #[test]
+    fn test_matching_manifest_passes() {
+        let tool = make_tool("lookup", Some("desc"), "{}");
+        let hash = fingerprint::compute_tool_fingerprint(&tool);
+        let manifest = vec![SbomComponent {
+            purl: "pkg:mcp/finance-tools/lookup@1.0.0".to_string(),
+            hash,
+        }];
+        let violations = check_provenance_against("finance-tools", &[tool], &manifest);
+        assert!(violations.is_empty());
+    }
+
+    #[test]
+    fn test_purl_matches_without_version_suffix() {
+        let tool = make_tool("lookup", Some("desc"), "{}");
+        let hash = fingerprint::compute_tool_fingerprint(&tool);
+        let manifest = vec![SbomComponent {
+            purl: "pkg:mcp/finance-tools/lookup".to_string(),
+            hash,
+        }];
+        let violations = check_provenance_against("finance-tools", &[tool], &manifest);
+        assert!(violations.is_empty());
+    }
+
+    #[test]
+    fn test_purl_does_not_match_different_tool_name_prefix() {
+        let tool = make_tool("lookup2", Some("desc"), "{}");
+        let manifest = vec![SbomComponent {
+            purl: "pkg:mcp/finance-tools/lookup@1.0.0".to_string(),
+            hash: "abc123".to_string(),
+        }];
+        let violations = check_provenance_against("finance-tools", &[tool], &manifest);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].code, "tool_not_in_sbom");
+    }
+}
@@ -43,6 +43,80 @@ pub fn get_settings_schema() -> String {
                             "title": "Tool Fingerprints",
                             "description": "Map of tool name to expected fingerprint hash for mimicry detection",
                             "additionalProperties": {"type": "string"}
+                        },
+                        "required_capability": {
+                            "type": "string",
+                            "title": "Required Capability",
+                            "description": "UCAN ability (e.g. 'mcp/invoke') a presented delegation chain must grant on this server's tools before invocation is allowed"
+                        },
+                        "trusted_issuer_did": {
+                            "type": "string",
+                            "title": "Trusted Issuer DID",
+                            "description": "did:key identifier of the resource owner; UCAN delegation chains must root at this issuer"
+                        },
+                        "trusted_issuer": {
+                            "type": "object",
+                            "title": "Trusted Issuer Key",
+                            "description": "Ed25519 public key (JWK) this server can prove possession of via a signed connection-time attestation token, establishing identity cryptographically rather than by name similarity",
+                            "properties": {
+                                "kty": {
+                                    "type": "string",
+                                    "title": "Key Type",
+                                    "description": "JWK key type; only 'OKP' (Octet Key Pair) is supported"
+                                },
+                                "crv": {
+                                    "type": "string",
+                                    "title": "Curve",
+                                    "description": "JWK curve; only 'Ed25519' is supported"
+                                },
+                                "x": {
+                                    "type": "string",
+                                    "title": "Public Key",
+                                    "description": "Base64url-encoded (unpadded) raw 32-byte Ed25519 public key"
+                                }
+                            },
+                            "required": ["kty", "crv", "x"]
+                        },
+                        "expected_protocol_version": {
+                            "type": "array",
+                            "title": "Expected Protocol Version",
+                            "description": "Minimum [major, minor] MCP protocol version this server must negotiate; a later connection reporting a lower version is flagged as a downgrade",
+                            "items": { "type": "integer" },
+                            "minItems": 2,
+                            "maxItems": 2
+                        },
+                        "expected_capabilities": {
+                            "type": "array",
+                            "title": "Expected Capabilities",
+                            "description": "Capability set this server is pinned to once observed; a later connection that gains or drops any of these is flagged as capability drift",
+                            "items": { "type": "string" }
+                        },
+                        "sbom_manifest": {
+                            "type": "object",
+                            "title": "SBOM Manifest",
+                            "description": "CycloneDX-style component manifest pinning this server's tools by package-URL and expected fingerprint hash",
+                            "properties": {
+                                "components": {
+                                    "type": "array",
+                                    "items": {
+                                        "type": "object",
+                                        "properties": {
+                                            "purl": {
+                                                "type": "string",
+                                                "description": "Package URL identifying the tool, e.g. 'pkg:mcp/finance-tools/lookup@1.0.0'"
+                                            },
+                                            "hash": {
+                                                "type": "string",
+                                                "description": "Expected output of compute_tool_fingerprint for this tool"
+                                            }
+                                        },
+                                        "required": ["purl", "hash"]
+                                    }
+                                }
+                            },
+                            "x-ui": {
+                                "component": "json-editor"
+                            }
                         }
                     },
                     "required": ["name"]
@@ -103,6 +177,29 @@ pub fn get_settings_schema() -> String {
                     "order": 6,
                     "group": "mimicry"
                 }
+            },
+            "require_sbom": {
+                "type": "boolean",
+                "title": "Require SBOM",
+                "description": "Deny a server's tools_list when it has no SBOM manifest, or when an advertised tool isn't covered by one, instead of allowing unchecked",
+                "default": false,
+                "x-ui": {
+                    "component": "checkbox",
+                    "order": 7,
+                    "group": "mimicry"
+                }
+            },
+            "custom_confusables": {
+                "type": "object",
+                "title": "Custom Confusables",
+                "description": "Additional confusable character or sequence mappings (e.g. a newly observed lookalike font rendering) merged with the built-in table without requiring a guard redeploy",
+                "additionalProperties": { "type": "string" },
+                "default": {},
+                "x-ui": {
+                    "component": "json-editor",
+                    "order": 8,
+                    "group": "typosquat"
+                }
             }
         },
         "x-ui-groups": {
@@ -142,7 +239,9 @@ pub fn get_default_config() -> String {
         "block_unknown_servers": true,
         "typosquat_detection_enabled": true,
         "typosquat_similarity_threshold": 0.85,
-        "tool_mimicry_detection_enabled": true
+        "tool_mimicry_detection_enabled": true,
+        "require_sbom": false,
+        "custom_confusables": {}
     })
     .to_string()
 }
@@ -186,6 +285,8 @@ mod tests {
             "typosquat_detection_enabled",
             "typosquat_similarity_threshold",
             "tool_mimicry_detection_enabled",
+            "require_sbom",
+            "custom_confusables",
         ];
         for key in expected {
             assert!(props.get(key).is_some(), "Missing property: {}", key);
@@ -220,6 +321,7 @@ mod tests {
         assert_eq!(val.get("typosquat_detection_enabled").unwrap(), true);
         assert_eq!(val.get("typosquat_similarity_threshold").unwrap(), 0.85);
         assert_eq!(val.get("tool_mimicry_detection_enabled").unwrap(), true);
+        assert_eq!(val.get("require_sbom").unwrap(), false);
         assert!(val.get("whitelist").unwrap().is_array());
     }
 
@@ -1,13 +1,32 @@
+use crate::bktree::BkTree;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// A server's protocol version and capability set as last observed at
+/// handshake, persisted across reconnects so drift can be detected even
+/// though each connection gets a fresh `GuardContext`.
+#[derive(Debug, Clone)]
+pub struct ServerHandshake {
+    pub protocol_version: (u16, u16),
+    pub capabilities: Vec<String>,
+}
 
 // Tool registry: server_name -> { tool_name -> fingerprint }
 // Config cache: parsed JSON from host
+// BK-tree cache: fuzzy-match index over whitelist names, keyed by a hash of
+// the whitelist so it rebuilds only when the config actually changes.
+// Handshake registry: server_name -> last-seen protocol version/capabilities.
 thread_local! {
     static TOOL_REGISTRY: RefCell<HashMap<String, HashMap<String, String>>> =
         RefCell::new(HashMap::new());
     static CONFIG_CACHE: RefCell<Option<serde_json::Value>> =
         RefCell::new(None);
+    static BKTREE_CACHE: RefCell<Option<(u64, BkTree)>> =
+        RefCell::new(None);
+    static HANDSHAKE_REGISTRY: RefCell<HashMap<String, ServerHandshake>> =
+        RefCell::new(HashMap::new());
 }
 
 pub fn get_tool_registry<F, R>(f: F) -> R
@@ -24,6 +43,18 @@ where
     TOOL_REGISTRY.with(|reg| f(&mut reg.borrow_mut()))
 }
 
+/// Look up the last-seen handshake recorded for `server_name`, if any.
+pub fn get_last_handshake(server_name: &str) -> Option<ServerHandshake> {
+    HANDSHAKE_REGISTRY.with(|reg| reg.borrow().get(server_name).cloned())
+}
+
+/// Record `server_name`'s handshake as the new last-seen value.
+pub fn record_handshake(server_name: &str, handshake: ServerHandshake) {
+    HANDSHAKE_REGISTRY.with(|reg| {
+        reg.borrow_mut().insert(server_name.to_string(), handshake);
+    });
+}
+
 pub fn get_cached_config() -> Option<serde_json::Value> {
     CONFIG_CACHE.with(|cache| cache.borrow().clone())
 }
@@ -33,3 +64,35 @@ pub fn set_cached_config(config: serde_json::Value) {
         *cache.borrow_mut() = Some(config);
     });
 }
+
+/// Hash a set of whitelist names to detect hot-reload config changes.
+fn hash_names<'a, I: IntoIterator<Item = &'a str>>(names: I) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for name in names {
+        name.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Run `f` against the BK-tree built from `names`, rebuilding it first if
+/// the whitelist has changed since the last call (hot-reload aware).
+pub fn with_whitelist_bktree<'a, F, R>(names: impl IntoIterator<Item = &'a str>, f: F) -> R
+where
+    F: FnOnce(&BkTree) -> R,
+{
+    let names: Vec<&str> = names.into_iter().collect();
+    let current_hash = hash_names(names.iter().copied());
+
+    BKTREE_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let needs_rebuild = match &*cache {
+            Some((hash, _)) => *hash != current_hash,
+            None => true,
+        };
+        if needs_rebuild {
+            let tree = BkTree::build(names.iter().map(|n| n.to_lowercase()));
+            *cache = Some((current_hash, tree));
+        }
+        f(&cache.as_ref().unwrap().1)
+    })
+}
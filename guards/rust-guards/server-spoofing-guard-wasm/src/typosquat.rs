@@ -1,93 +1,307 @@
+use crate::bktree;
 use crate::config;
 use crate::levenshtein;
+use crate::state;
+use std::collections::{HashMap, HashSet};
+use unicode_normalization::UnicodeNormalization;
+
+/// Which detection path flagged a name, for caller logging/auditing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectionPath {
+    /// Confusables-folded skeletons are identical (e.g. Cyrillic `раypal` vs `paypal`).
+    SkeletonMatch,
+    /// Suspect mixes scripts with an otherwise single-script approved name.
+    MixedScript,
+    /// Same length, exactly one character differs (e.g. `finance-to0ls`).
+    SingleCharSubstitution,
+    /// Overall similarity clears the threshold but the mutation doesn't fit
+    /// a more specific path above (e.g. a transposed pair or an inserted
+    /// character, which change the diff count or the length and so can't be
+    /// a single-character substitution).
+    EditDistance,
+}
+
+impl DetectionPath {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DetectionPath::SkeletonMatch => "skeleton_match",
+            DetectionPath::MixedScript => "mixed_script",
+            DetectionPath::SingleCharSubstitution => "single_char_substitution",
+            DetectionPath::EditDistance => "edit_distance",
+        }
+    }
+}
+
+/// A detected typosquat, naming the approved server it was confused with and
+/// which detection path fired.
+pub struct TyposquatMatch {
+    pub approved_name: String,
+    pub detection_path: DetectionPath,
+    /// The suspect name's folded confusables skeleton, surfaced so an
+    /// operator reviewing a deny can see exactly what it was reduced to.
+    pub matched_skeleton: String,
+}
 
 /// Detect if server_name is a typosquat of an approved server.
-/// Returns `Some(approved_name)` if detected, `None` otherwise.
 ///
-/// Matches Python `_detect_typosquat` from `app.py`.
-pub fn detect_typosquat(server_name: &str) -> Option<String> {
+/// Skeleton equality is checked against the *full* whitelist first: folded
+/// confusables can collide no matter how far apart the raw strings are under
+/// Levenshtein distance (that's the whole point of `SkeletonMatch`), so
+/// pruning candidates by raw edit distance before this check would defeat
+/// it — a two-character Cyrillic substitution can easily exceed the
+/// distance bound a tight similarity threshold implies. The mixed-script
+/// and single-character-substitution paths, by contrast, are themselves
+/// gated on a minimum Levenshtein-ratio threshold, so pre-filtering their
+/// candidates through a BK-tree (see `bktree`) keyed by edit distance only
+/// discards entries that would fail the threshold check anyway — that
+/// narrowing is safe, and keeps the common case from scanning the whole
+/// whitelist.
+pub fn detect_typosquat(server_name: &str) -> Option<TyposquatMatch> {
     let threshold = config::get_threshold();
     let whitelist = config::get_whitelist();
+    let custom_confusables = config::get_custom_confusables();
     let test_name = server_name.to_lowercase();
+    let test_skeleton = confusable_skeleton_with(&test_name, &custom_confusables);
+    let test_scripts = scripts_in(server_name);
 
-    for entry in &whitelist {
-        let approved_name = entry.name.to_lowercase();
+    if let Some(m) = whitelist
+        .iter()
+        .find_map(|entry| skeleton_match(entry, &test_name, &test_skeleton, &custom_confusables))
+    {
+        return Some(m);
+    }
 
-        // Skip exact matches
-        if approved_name == test_name {
-            continue;
-        }
+    let max_len = whitelist
+        .iter()
+        .map(|e| e.name.chars().count())
+        .chain(std::iter::once(test_name.chars().count()))
+        .max()
+        .unwrap_or(0);
+    let max_distance = bktree::threshold_to_max_distance(threshold, max_len);
+
+    let names: Vec<&str> = whitelist.iter().map(|e| e.name.as_str()).collect();
+    let candidates: Vec<String> = state::with_whitelist_bktree(names, |tree| {
+        tree.find_within(&test_name, max_distance)
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect()
+    });
+
+    whitelist
+        .iter()
+        .filter(|e| candidates.contains(&e.name.to_lowercase()))
+        .find_map(|entry| {
+            evaluate_candidate(
+                entry,
+                &test_name,
+                &test_skeleton,
+                &test_scripts,
+                threshold,
+                &custom_confusables,
+            )
+        })
+}
 
-        let similarity = levenshtein::levenshtein_ratio(&approved_name, &test_name);
+/// The `SkeletonMatch` check, pulled out so `detect_typosquat` can run it
+/// against the full whitelist before any distance-based pruning happens
+/// (see its doc comment), while `evaluate_candidate` still runs it inline
+/// for the fuzzing harness and BK-tree-filtered candidates.
+fn skeleton_match(
+    entry: &config::WhitelistEntry,
+    test_name: &str,
+    test_skeleton: &str,
+    custom_confusables: &HashMap<String, String>,
+) -> Option<TyposquatMatch> {
+    let approved_name = entry.name.to_lowercase();
+    if approved_name == test_name {
+        return None;
+    }
+
+    let approved_skeleton = confusable_skeleton_with(&approved_name, custom_confusables);
+    if approved_skeleton == test_skeleton {
+        Some(TyposquatMatch {
+            approved_name: entry.name.clone(),
+            detection_path: DetectionPath::SkeletonMatch,
+            matched_skeleton: test_skeleton.to_string(),
+        })
+    } else {
+        None
+    }
+}
 
-        if similarity >= threshold && is_typosquat_pattern(&approved_name, &test_name) {
-            return Some(entry.name.clone());
+/// Check a single approved entry against a suspect name's precomputed
+/// lowercase form, confusables skeleton, and script set. Pulled out of
+/// `detect_typosquat` so it can be driven directly (e.g. by the fuzzing
+/// harness in `fuzzing`) without needing host config or the BK-tree cache.
+pub(crate) fn evaluate_candidate(
+    entry: &config::WhitelistEntry,
+    test_name: &str,
+    test_skeleton: &str,
+    test_scripts: &HashSet<&'static str>,
+    threshold: f64,
+    custom_confusables: &HashMap<String, String>,
+) -> Option<TyposquatMatch> {
+    let approved_name = entry.name.to_lowercase();
+    if approved_name == test_name {
+        return None;
+    }
+
+    // High-confidence path: folded skeletons collide regardless of threshold.
+    if let Some(m) = skeleton_match(entry, test_name, test_skeleton, custom_confusables) {
+        return Some(m);
+    }
+
+    let approved_skeleton = confusable_skeleton_with(&approved_name, custom_confusables);
+
+    // A single-script approved name confused with a mixed-script suspect
+    // whose skeleton is still a close match is a classic homoglyph attack,
+    // independent of the raw Levenshtein ratio on the original strings.
+    let approved_scripts = scripts_in(&entry.name);
+    if is_single_script(&approved_scripts, "Latin") && mixes_scripts(test_scripts) {
+        let skeleton_similarity = levenshtein::levenshtein_ratio(&approved_skeleton, test_skeleton);
+        if skeleton_similarity >= threshold {
+            return Some(TyposquatMatch {
+                approved_name: entry.name.clone(),
+                detection_path: DetectionPath::MixedScript,
+                matched_skeleton: test_skeleton.to_string(),
+            });
         }
     }
 
+    // General fallback: any mutation shape (transposition, insertion,
+    // deletion, ...) whose overall similarity still clears the threshold.
+    // Single-character substitutions get their own, more specific path;
+    // everything else above the bar falls through to this one.
+    let similarity = levenshtein::levenshtein_ratio(&approved_name, test_name);
+    if similarity >= threshold {
+        let detection_path = if is_single_char_substitution(&approved_name, test_name) {
+            DetectionPath::SingleCharSubstitution
+        } else {
+            DetectionPath::EditDistance
+        };
+        return Some(TyposquatMatch {
+            approved_name: entry.name.clone(),
+            detection_path,
+            matched_skeleton: test_skeleton.to_string(),
+        });
+    }
+
     None
 }
 
-/// Check for common typosquat patterns: single-char substitution and homoglyphs.
-///
-/// Matches Python `_is_typosquat_pattern` from `app.py`.
-fn is_typosquat_pattern(approved: &str, suspect: &str) -> bool {
+/// Same length, exactly one character differs.
+pub(crate) fn is_single_char_substitution(approved: &str, suspect: &str) -> bool {
     let approved_chars: Vec<char> = approved.chars().collect();
     let suspect_chars: Vec<char> = suspect.chars().collect();
-
-    // Check single character substitution (same length, exactly 1 diff)
-    if approved_chars.len() == suspect_chars.len() {
-        let diffs = approved_chars
-            .iter()
-            .zip(suspect_chars.iter())
-            .filter(|(a, b)| a != b)
-            .count();
-        if diffs == 1 {
-            return true;
-        }
+    if approved_chars.len() != suspect_chars.len() {
+        return false;
     }
+    let diffs = approved_chars
+        .iter()
+        .zip(suspect_chars.iter())
+        .filter(|(a, b)| a != b)
+        .count();
+    diffs == 1
+}
 
-    // Check homoglyph attacks (visually similar characters)
-    let normalized = normalize_homoglyphs(suspect);
-    if approved == normalized && approved != suspect {
-        return true;
-    }
+/// Fold a name to its confusables "skeleton": NFKC-normalize, map each
+/// character through `confusable_prototype` to its Latin look-alike (a
+/// curated table of common Cyrillic/Greek/digit substitutions, not the full
+/// Unicode confusables data set — see its doc comment), then fold
+/// multi-character lookalike sequences (see `MULTI_CHAR_FOLDS`). Two names
+/// with equal skeletons are indistinguishable to the extent this table's
+/// UTS #39-style approach covers; a code point the table doesn't know about
+/// folds to itself.
+pub fn confusable_skeleton(s: &str) -> String {
+    let folded: String = s.nfkc().map(confusable_prototype).collect();
+    apply_multi_char_folds(&folded)
+}
 
-    false
+/// Like `confusable_skeleton`, but also applies operator-supplied
+/// confusable sequences from `custom` (see `config::get_custom_confusables`)
+/// on top of the built-in table, so new lookalikes seen in the wild can be
+/// added without a guard redeploy.
+pub fn confusable_skeleton_with(s: &str, custom: &HashMap<String, String>) -> String {
+    let mut skeleton = confusable_skeleton(s);
+    for (from, to) in custom {
+        skeleton = skeleton.replace(from.as_str(), to.as_str());
+    }
+    skeleton
 }
 
-/// Normalize homoglyphs by replacing visually similar characters.
-///
-/// Applies replacements in the same order as the Python implementation:
-///   'o': ['0'], 'l': ['1', 'I', '|'], 'i': ['1', 'l', '|'], 'a': ['@'], 'e': ['3']
-///
-/// Note: The Python iteration order causes cascading effects (e.g., 'l' subs happen
-/// before 'i' subs, so 'l' -> 'i' replaces ALL 'l' characters including originals).
-fn normalize_homoglyphs(s: &str) -> String {
-    let mut result = s.to_string();
+/// Multi-character sequences that render near-identically to a single
+/// character in most fonts (e.g. "rn" vs "m"), which NFKC + per-char folding
+/// can't catch since neither sequence decomposes to the other.
+const MULTI_CHAR_FOLDS: &[(&str, &str)] = &[("rn", "m"), ("vv", "w"), ("cl", "d")];
 
-    // 'o': ['0']
-    result = result.replace('0', "o");
+fn apply_multi_char_folds(s: &str) -> String {
+    let mut result = s.to_string();
+    for (from, to) in MULTI_CHAR_FOLDS {
+        result = result.replace(from, to);
+    }
+    result
+}
 
-    // 'l': ['1', 'I', '|']
-    result = result.replace('1', "l");
-    result = result.replace('I', "l");
-    result = result.replace('|', "l");
+/// Map a single character to its confusables prototype. This is a curated
+/// table of the common Cyrillic/Greek lookalikes and digit substitutions
+/// seen in typosquatting, not a full port of the Unicode Consortium's
+/// `confusablesSummary.txt` (that file maps tens of thousands of code
+/// points across many more scripts); operators who hit a lookalike this
+/// table misses can add it here, or via `get_custom_confusables` without a
+/// guard redeploy.
+fn confusable_prototype(c: char) -> char {
+    match c {
+        // Cyrillic lookalikes.
+        '\u{0430}' => 'a', // а CYRILLIC SMALL LETTER A
+        '\u{0435}' => 'e', // е CYRILLIC SMALL LETTER IE
+        '\u{043E}' => 'o', // о CYRILLIC SMALL LETTER O
+        '\u{0440}' => 'p', // р CYRILLIC SMALL LETTER ER
+        '\u{0441}' => 'c', // с CYRILLIC SMALL LETTER ES
+        '\u{0445}' => 'x', // х CYRILLIC SMALL LETTER HA
+        '\u{0443}' => 'y', // у CYRILLIC SMALL LETTER U
+        '\u{0456}' => 'i', // і CYRILLIC SMALL LETTER BYELORUSSIAN-UKRAINIAN I
+        '\u{0458}' => 'j', // ј CYRILLIC SMALL LETTER JE
+        '\u{04BB}' => 'h', // һ CYRILLIC SMALL LETTER SHHA
+        // Greek lookalikes.
+        '\u{03BF}' => 'o', // ο GREEK SMALL LETTER OMICRON
+        '\u{03B1}' => 'a', // α GREEK SMALL LETTER ALPHA
+        '\u{03B5}' => 'e', // ε GREEK SMALL LETTER EPSILON
+        '\u{03C1}' => 'p', // ρ GREEK SMALL LETTER RHO
+        '\u{03BD}' => 'v', // ν GREEK SMALL LETTER NU
+        '\u{03C5}' => 'u', // υ GREEK SMALL LETTER UPSILON
+        // Digit/letter confusables (NFKC doesn't fold these).
+        '0' => 'o',
+        '1' => 'l',
+        '|' => 'l',
+        _ => c,
+    }
+}
 
-    // 'i': ['1', 'l', '|']
-    // After the previous step, '1' and '|' are already replaced.
-    // But 'l' -> 'i' converts ALL remaining 'l' chars (including originals).
-    result = result.replace('1', "i"); // no-op (already replaced above)
-    result = result.replace('l', "i"); // converts ALL 'l' to 'i'
-    result = result.replace('|', "i"); // no-op (already replaced above)
+/// Unicode scripts this matcher distinguishes; anything else is "Other".
+fn script_of(c: char) -> &'static str {
+    match c as u32 {
+        0x0041..=0x005A | 0x0061..=0x007A | 0x00C0..=0x024F => "Latin",
+        0x0400..=0x04FF => "Cyrillic",
+        0x0370..=0x03FF => "Greek",
+        _ => "Other",
+    }
+}
 
-    // 'a': ['@']
-    result = result.replace('@', "a");
+/// Compute the set of scripts (excluding "Other", which covers digits,
+/// punctuation and separators) present in `s`.
+pub(crate) fn scripts_in(s: &str) -> HashSet<&'static str> {
+    s.chars()
+        .map(script_of)
+        .filter(|&script| script != "Other")
+        .collect()
+}
 
-    // 'e': ['3']
-    result = result.replace('3', "e");
+fn is_single_script(scripts: &HashSet<&'static str>, name: &str) -> bool {
+    scripts.len() == 1 && scripts.contains(name)
+}
 
-    result
+fn mixes_scripts(scripts: &HashSet<&'static str>) -> bool {
+    scripts.len() > 1
 }
 
 #[cfg(test)]
@@ -95,81 +309,112 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_is_typosquat_single_char_substitution() {
-        assert!(is_typosquat_pattern("finance-tools", "finance-toals"));
+    fn test_single_char_substitution() {
+        assert!(is_single_char_substitution("finance-tools", "finance-toals"));
+    }
+
+    #[test]
+    fn test_single_char_substitution_zero_for_o() {
+        assert!(is_single_char_substitution("company-tools", "c0mpany-tools"));
+    }
+
+    #[test]
+    fn test_not_single_char_substitution_completely_different() {
+        assert!(!is_single_char_substitution("finance-tools", "weather-api"));
+    }
+
+    #[test]
+    fn test_skeleton_folds_cyrillic_lookalikes() {
+        // CYRILLIC SMALL LETTER ER (р) + CYRILLIC SMALL LETTER A (а) + "ypal"
+        let suspect = "\u{0440}\u{0430}ypal";
+        assert_eq!(confusable_skeleton(suspect), confusable_skeleton("paypal"));
+    }
+
+    #[test]
+    fn test_skeleton_folds_digits() {
+        assert_eq!(confusable_skeleton("c0mpany-to0ls"), "company-tools");
     }
 
     #[test]
-    fn test_is_typosquat_homoglyph_zero_for_o() {
-        // "c0mpany-tools" -> after normalization: "company-toois"
-        // "company-tools" -> after normalization they should match?
-        // Actually, let's trace: normalize("c0mpany-tools"):
-        //   '0'->'o': "company-tools"
-        //   '1'->'l': "company-tools"
-        //   'I'->'l': "company-tools"
-        //   '|'->'l': "company-tools"
-        //   '1'->'i': "company-tools"
-        //   'l'->'i': "company-toois"
-        //   '|'->'i': "company-toois"
-        //   '@'->'a': "company-toois"
-        //   '3'->'e': "company-toois"
-        //
-        // approved = "company-tools", normalized suspect = "company-toois"
-        // These don't match, so homoglyph path returns false.
-        //
-        // But single-char substitution: "company-tools" vs "c0mpany-tools"
-        // Both have 14 chars, diff at position 1 ('o' vs '0'), diffs = 1 -> true
-        assert!(is_typosquat_pattern("company-tools", "c0mpany-tools"));
+    fn test_skeleton_folds_multi_char_rn_as_m() {
+        assert_eq!(confusable_skeleton("modern"), confusable_skeleton("modem"));
     }
 
     #[test]
-    fn test_is_typosquat_homoglyph_one_for_l() {
-        // "finance-tools" vs "finance-too1s"
-        // Same length, diff at position 11 ('l' vs '1'), diffs = 1 -> true
-        assert!(is_typosquat_pattern("finance-tools", "finance-too1s"));
+    fn test_skeleton_folds_multi_char_vv_as_w() {
+        assert_eq!(confusable_skeleton("vvorkspace"), confusable_skeleton("workspace"));
     }
 
     #[test]
-    fn test_not_typosquat_completely_different() {
-        assert!(!is_typosquat_pattern("finance-tools", "weather-api"));
+    fn test_custom_confusables_applied_on_top_of_builtin() {
+        let mut custom = HashMap::new();
+        custom.insert("ph".to_string(), "f".to_string());
+        assert_eq!(
+            confusable_skeleton_with("phinance-tools", &custom),
+            confusable_skeleton_with("finance-tools", &custom)
+        );
     }
 
     #[test]
-    fn test_normalize_homoglyphs_zero_for_o() {
-        let result = normalize_homoglyphs("c0mpany");
-        // '0'->'o': "company", then 'l'->'i' converts all 'l' (none here)
-        // But wait: 'l'->'i' happens, converting original 'l' in result... no 'l' in "company"
-        // Actually there is no 'l' in "company", so result = "company"
-        // Hmm but the 'i' step replaces 'l' with 'i'. "company" has no 'l'. So stays "company".
-        // Wait, I need to re-trace more carefully.
-        //
-        // After all steps: "c0mpany" -> "company" (o step) -> no l/I/| changes
-        // -> 'l'->'i' but no 'l' exists -> "company" -> no @/3 -> "company"
-        //
-        // But then normalize_homoglyphs("company") should also convert 'l' to 'i':
-        // "company" has no 'l', so it stays "company"
-        //
-        // So normalize("c0mpany") = "company" âœ“
-        assert_eq!(result, "company");
+    fn test_custom_confusables_empty_matches_builtin_skeleton() {
+        let no_custom = HashMap::new();
+        assert_eq!(
+            confusable_skeleton_with("paypal", &no_custom),
+            confusable_skeleton("paypal")
+        );
     }
 
     #[test]
-    fn test_normalize_homoglyphs_cascading() {
-        // Demonstrate the cascading l->i effect
-        // "hello" -> '0': "hello" -> '1': "hello" -> 'I': "hello" -> '|': "hello"
-        // -> 'l'->'i': "heiio" -> ...
-        let result = normalize_homoglyphs("hello");
-        assert_eq!(result, "heiio");
+    fn test_scripts_in_detects_mixed() {
+        let scripts = scripts_in("\u{0440}aypal");
+        assert!(mixes_scripts(&scripts));
     }
 
     #[test]
-    fn test_normalize_homoglyphs_at_for_a() {
-        let result = normalize_homoglyphs("@dmin");
-        // '@'->'a': "admin", 'l'->'i' (no l): "admin"
-        // But wait, order matters. Let me trace:
-        // "0": no '0', "1": no '1', "I": no 'I', "|": no '|',
-        // "1"->i no-op, "l"->i: no 'l', "|"->i no-op,
-        // "@"->"a": "admin", "3"->e: "admin"
-        assert_eq!(result, "admin");
+    fn test_scripts_in_single_latin() {
+        let scripts = scripts_in("paypal");
+        assert!(is_single_script(&scripts, "Latin"));
+    }
+
+    fn whitelist_entry(name: &str) -> config::WhitelistEntry {
+        config::WhitelistEntry {
+            name: name.to_string(),
+            url_pattern: None,
+            tool_fingerprints: Default::default(),
+            required_capability: None,
+            trusted_issuer_did: None,
+            trusted_issuer: None,
+            expected_protocol_version: None,
+            expected_capabilities: None,
+            sbom_manifest: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_skeleton_match_fires_regardless_of_bktree_distance_bound() {
+        // "раypal" (Cyrillic ER + Cyrillic A + "ypal") is raw Levenshtein
+        // distance 2 from "paypal", but at the default 0.85 similarity
+        // threshold the BK-tree's distance bound for a 6-char name is only
+        // ceil(0.15 * 6) = 1. A BK-tree prefilter keyed on that bound would
+        // wrongly exclude "paypal" as a candidate before this distance-
+        // independent skeleton check ever ran.
+        let approved = whitelist_entry("paypal");
+        let suspect = "\u{0440}\u{0430}ypal";
+        let test_name = suspect.to_lowercase();
+        let no_custom = HashMap::new();
+        let test_skeleton = confusable_skeleton_with(&test_name, &no_custom);
+
+        let raw_distance = levenshtein::levenshtein_distance("paypal", &test_name);
+        let bound = bktree::threshold_to_max_distance(0.85, 6);
+        assert!(
+            raw_distance > bound,
+            "expected the BK-tree bound to be tighter than the raw distance for this case"
+        );
+
+        let result = skeleton_match(&approved, &test_name, &test_skeleton, &no_custom);
+        assert!(
+            result.is_some(),
+            "skeleton match must fire even though raw edit distance exceeds the BK-tree bound"
+        );
     }
 }
@@ -0,0 +1,447 @@
+//! UCAN-style capability delegation for tool invocation authorization.
+//!
+//! A UCAN ("User Controlled Authorization Network") token is a signed,
+//! JWT-shaped credential: `base64url(header).base64url(claims).base64url(sig)`.
+//! Its claims carry `iss` (issuer DID), `aud` (audience DID), `exp`/`nbf` time
+//! bounds, an `att` array of `{with, can}` capabilities, and a `prf` array of
+//! parent token strings that the issuer was delegated by. Validating a token
+//! means walking `prf` back to a root whose `iss` is the resource owner,
+//! verifying every signature along the way, and checking that each link only
+//! narrows (never widens) the capabilities of the link below it.
+
+/// One delegated capability: `{with: resource, can: ability}`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Capability {
+    pub with: String,
+    pub can: String,
+}
+
+/// Decoded UCAN claims.
+#[derive(Debug, Clone, serde::Deserialize)]
+struct UcanClaims {
+    iss: String,
+    aud: String,
+    exp: i64,
+    #[serde(default)]
+    nbf: i64,
+    att: Vec<Capability>,
+    #[serde(default)]
+    prf: Vec<String>,
+}
+
+/// Result of a successful chain validation.
+pub struct Authorization {
+    /// DID of the root issuer the chain bottoms out at.
+    pub root_issuer: String,
+}
+
+/// Validate a UCAN delegation chain and check it grants `required` on `resource`.
+///
+/// `token` is the capability token presented for the invocation, `aud` is this
+/// gateway's own DID, and `now` is the host-provided clock (seconds since epoch).
+/// `owner_did` is the `trusted_issuer_did` of the *specific* whitelist entry
+/// that owns `resource` — callers must resolve this themselves (e.g. by
+/// matching `resource`'s server name against their whitelist) rather than
+/// passing the whole whitelist, so a chain rooted at some other entry's DID
+/// can't forge a capability for a resource it doesn't own.
+/// Returns `Ok(Authorization)` when a complete, unexpired, attenuating chain
+/// reaches a root issuer matching `owner_did`; otherwise a human-readable
+/// reason suitable for a deny message.
+pub fn authorize(
+    token: &str,
+    resource: &str,
+    required: &str,
+    aud: &str,
+    now: i64,
+    owner_did: Option<&str>,
+) -> Result<Authorization, String> {
+    let mut chain = Vec::new();
+    let mut current = token.to_string();
+    let mut expected_aud = aud.to_string();
+
+    loop {
+        let (claims, sig_input, sig) = decode_token(&current)?;
+
+        if !verify_signature(&claims.iss, &sig_input, &sig) {
+            return Err(format!("invalid signature from issuer '{}'", claims.iss));
+        }
+        if claims.aud != expected_aud {
+            return Err(format!(
+                "audience mismatch: token for '{}', expected '{}'",
+                claims.aud, expected_aud
+            ));
+        }
+        if now < claims.nbf {
+            return Err("token not yet valid (nbf)".to_string());
+        }
+        if now >= claims.exp {
+            return Err("token expired".to_string());
+        }
+
+        chain.push(claims.clone());
+
+        match claims.prf.first() {
+            Some(parent) => {
+                // The delegatee of the parent link must be this link's issuer.
+                expected_aud = claims.iss.clone();
+                current = parent.clone();
+            }
+            None => break,
+        }
+    }
+
+    // Every link must attenuate (never widen) the capabilities of its parent.
+    for window in chain.windows(2) {
+        let (child, parent) = (&window[0], &window[1]);
+        if !is_attenuation(&child.att, &parent.att) {
+            return Err(format!(
+                "capability widening detected between '{}' and parent '{}'",
+                child.iss, parent.iss
+            ));
+        }
+    }
+
+    let root = chain.last().ok_or("empty delegation chain")?;
+    let owns_resource = owner_did == Some(root.iss.as_str());
+    if !owns_resource {
+        return Err(format!(
+            "root issuer '{}' does not own resource '{}'",
+            root.iss, resource
+        ));
+    }
+
+    let grants = chain.iter().any(|link| {
+        link.att
+            .iter()
+            .any(|cap| resource_covers(&cap.with, resource) && cap.can == required)
+    });
+    if !grants {
+        return Err(format!(
+            "no capability in chain grants '{}' on '{}'",
+            required, resource
+        ));
+    }
+
+    Ok(Authorization {
+        root_issuer: root.iss.clone(),
+    })
+}
+
+/// A capability set is an attenuation of `parent` if every entry in `child`
+/// is covered by some entry in `parent` — same ability, and the child's
+/// resource no broader than the parent's (attenuation may only narrow,
+/// never add scope; see `resource_covers`).
+fn is_attenuation(child: &[Capability], parent: &[Capability]) -> bool {
+    child
+        .iter()
+        .all(|c| parent.iter().any(|p| resource_covers(&p.with, &c.with) && p.can == c.can))
+}
+
+/// Does a capability scoped `with: parent_resource` cover `resource`?
+/// Exact matches always do; a trailing `*` additionally covers any resource
+/// sharing that prefix, e.g. `"mcp://finance-tools/*"` covers
+/// `"mcp://finance-tools/read"` — the wildcard-scoped-root delegation this
+/// module's doc comment and tests describe. A literal `*` in the middle or
+/// start of a pattern is not special; only a trailing one is.
+fn resource_covers(parent_resource: &str, resource: &str) -> bool {
+    match parent_resource.strip_suffix('*') {
+        Some(prefix) => resource.starts_with(prefix),
+        None => parent_resource == resource,
+    }
+}
+
+/// Split a compact UCAN token into claims, signing input, and signature bytes.
+fn decode_token(token: &str) -> Result<(UcanClaims, String, Vec<u8>), String> {
+    let mut parts = token.splitn(3, '.');
+    let header = parts.next().ok_or("missing header segment")?;
+    let payload = parts.next().ok_or("missing payload segment")?;
+    let sig = parts.next().ok_or("missing signature segment")?;
+
+    let payload_bytes = base64url_decode(payload)?;
+    let claims: UcanClaims =
+        serde_json::from_slice(&payload_bytes).map_err(|e| format!("bad claims JSON: {e}"))?;
+    let sig_bytes = base64url_decode(sig)?;
+
+    Ok((claims, format!("{header}.{payload}"), sig_bytes))
+}
+
+/// Verify an Ed25519 signature over `signing_input`, using the public key
+/// embedded in the issuer's `did:key:` identifier (multicodec 0xed01).
+fn verify_signature(issuer_did: &str, signing_input: &str, sig: &[u8]) -> bool {
+    let Some(pubkey_bytes) = decode_did_key(issuer_did) else {
+        return false;
+    };
+    let Ok(verifying_key) = ed25519_dalek::VerifyingKey::from_bytes(
+        pubkey_bytes.as_slice().try_into().unwrap_or(&[0u8; 32]),
+    ) else {
+        return false;
+    };
+    let Ok(signature) = ed25519_dalek::Signature::from_slice(sig) else {
+        return false;
+    };
+    use ed25519_dalek::Verifier;
+    verifying_key
+        .verify(signing_input.as_bytes(), &signature)
+        .is_ok()
+}
+
+/// Extract the raw Ed25519 public key bytes from a `did:key:z...` identifier.
+/// `z` marks base58btc; the decoded bytes are a 2-byte multicodec prefix
+/// (`0xed 0x01` for Ed25519) followed by the 32-byte key.
+fn decode_did_key(did: &str) -> Option<Vec<u8>> {
+    let encoded = did.strip_prefix("did:key:z")?;
+    let decoded = base58_decode(encoded)?;
+    if decoded.len() != 34 || decoded[0] != 0xed || decoded[1] != 0x01 {
+        return None;
+    }
+    Some(decoded[2..].to_vec())
+}
+
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Minimal base58btc decoder, just enough for `did:key` identifiers.
+/// Not pulled in as a dependency since this is the only call site.
+fn base58_decode(s: &str) -> Option<Vec<u8>> {
+    let mut bytes = vec![0u8; s.len()];
+    let mut len = 0usize;
+    for ch in s.bytes() {
+        let mut carry = BASE58_ALPHABET.iter().position(|&c| c == ch)? as u32;
+        for b in bytes.iter_mut().take(len) {
+            carry += (*b as u32) * 58;
+            *b = (carry & 0xff) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes[len] = (carry & 0xff) as u8;
+            carry >>= 8;
+            len += 1;
+        }
+    }
+    // Leading '1's encode leading zero bytes.
+    let leading_zeros = s.bytes().take_while(|&c| c == b'1').count();
+    let mut out = vec![0u8; leading_zeros];
+    out.extend(bytes[..len].iter().rev());
+    Some(out)
+}
+
+fn base64url_decode(s: &str) -> Result<Vec<u8>, String> {
+    use base64::Engine;
+    base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(s)
+        .map_err(|e| format!("bad base64url: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_attenuation_subset_allowed() {
+        let parent = vec![
+            Capability { with: "mcp://finance-tools/*".into(), can: "mcp/invoke".into() },
+        ];
+        let child = vec![
+            Capability { with: "mcp://finance-tools/*".into(), can: "mcp/invoke".into() },
+        ];
+        assert!(is_attenuation(&child, &parent));
+    }
+
+    #[test]
+    fn test_is_attenuation_widening_rejected() {
+        let parent = vec![
+            Capability { with: "mcp://finance-tools/read".into(), can: "mcp/invoke".into() },
+        ];
+        let child = vec![
+            Capability { with: "mcp://finance-tools/transfer".into(), can: "mcp/invoke".into() },
+        ];
+        assert!(!is_attenuation(&child, &parent));
+    }
+
+    #[test]
+    fn test_is_attenuation_narrows_wildcard_root_to_concrete_resource() {
+        let parent = vec![
+            Capability { with: "mcp://finance-tools/*".into(), can: "mcp/invoke".into() },
+        ];
+        let child = vec![
+            Capability { with: "mcp://finance-tools/read".into(), can: "mcp/invoke".into() },
+        ];
+        assert!(is_attenuation(&child, &parent));
+    }
+
+    #[test]
+    fn test_is_attenuation_rejects_escape_from_wildcard_scope() {
+        let parent = vec![
+            Capability { with: "mcp://finance-tools/*".into(), can: "mcp/invoke".into() },
+        ];
+        let child = vec![
+            Capability { with: "mcp://other-server/read".into(), can: "mcp/invoke".into() },
+        ];
+        assert!(!is_attenuation(&child, &parent));
+    }
+
+    #[test]
+    fn test_resource_covers_exact_and_wildcard() {
+        assert!(resource_covers("mcp://finance-tools/read", "mcp://finance-tools/read"));
+        assert!(resource_covers("mcp://finance-tools/*", "mcp://finance-tools/read"));
+        assert!(resource_covers("mcp://finance-tools/*", "mcp://finance-tools/*"));
+        assert!(!resource_covers("mcp://finance-tools/read", "mcp://finance-tools/write"));
+        assert!(!resource_covers("mcp://finance-tools/*", "mcp://other-server/read"));
+    }
+
+    #[test]
+    fn test_decode_did_key_rejects_bad_prefix() {
+        assert!(decode_did_key("did:web:example.com").is_none());
+    }
+
+    #[test]
+    fn test_base58_decode_roundtrip_known_vector() {
+        // "Hello World" -> "JxF12TrwUP45BMd" per common base58 test vectors.
+        let decoded = base58_decode("JxF12TrwUP45BMd").unwrap();
+        assert_eq!(decoded, b"Hello World");
+    }
+
+    /// Test-only base58btc encoder, the inverse of `base58_decode`, used
+    /// below to build `did:key:z...` identifiers from a keypair generated
+    /// in-test (production only ever decodes a presented DID, never builds
+    /// one).
+    fn base58_encode(bytes: &[u8]) -> String {
+        let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+        let mut digits: Vec<u8> = vec![0];
+        for &byte in bytes {
+            let mut carry = byte as u32;
+            for digit in digits.iter_mut() {
+                carry += (*digit as u32) << 8;
+                *digit = (carry % 58) as u8;
+                carry /= 58;
+            }
+            while carry > 0 {
+                digits.push((carry % 58) as u8);
+                carry /= 58;
+            }
+        }
+        let mut out = String::new();
+        out.extend(std::iter::repeat_n('1', zeros));
+        out.extend(digits.iter().rev().map(|&d| BASE58_ALPHABET[d as usize] as char));
+        out
+    }
+
+    fn did_key_for(verifying_key: &ed25519_dalek::VerifyingKey) -> String {
+        let mut multicodec = vec![0xed, 0x01];
+        multicodec.extend_from_slice(&verifying_key.to_bytes());
+        format!("did:key:z{}", base58_encode(&multicodec))
+    }
+
+    fn sign_token(
+        signing_key: &ed25519_dalek::SigningKey,
+        iss: &str,
+        aud: &str,
+        exp: i64,
+        att: &[Capability],
+        prf: &[String],
+    ) -> String {
+        use base64::Engine;
+        use ed25519_dalek::Signer;
+
+        let header = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(b"{}");
+        let claims = serde_json::json!({
+            "iss": iss,
+            "aud": aud,
+            "exp": exp,
+            "att": att,
+            "prf": prf,
+        })
+        .to_string();
+        let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(claims);
+        let signing_input = format!("{header}.{payload}");
+        let sig = signing_key.sign(signing_input.as_bytes());
+        let sig_b64 = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(sig.to_bytes());
+        format!("{signing_input}.{sig_b64}")
+    }
+
+    #[test]
+    fn test_authorize_narrows_wildcard_root_to_concrete_resource() {
+        use ed25519_dalek::SigningKey;
+
+        let root_key = SigningKey::from_bytes(&[1u8; 32]);
+        let child_key = SigningKey::from_bytes(&[2u8; 32]);
+        let root_did = did_key_for(&root_key.verifying_key());
+        let child_did = did_key_for(&child_key.verifying_key());
+        let gateway_did = "did:key:zGatewayPlaceholder".to_string();
+
+        let root_att = vec![Capability {
+            with: "mcp://finance-tools/*".into(),
+            can: "mcp/invoke".into(),
+        }];
+        let root_token = sign_token(&root_key, &root_did, &child_did, 9_999_999_999, &root_att, &[]);
+
+        let child_att = vec![Capability {
+            with: "mcp://finance-tools/read".into(),
+            can: "mcp/invoke".into(),
+        }];
+        let child_token = sign_token(
+            &child_key,
+            &child_did,
+            &gateway_did,
+            9_999_999_999,
+            &child_att,
+            &[root_token],
+        );
+
+        let result = authorize(
+            &child_token,
+            "mcp://finance-tools/read",
+            "mcp/invoke",
+            &gateway_did,
+            0,
+            Some(&root_did),
+        );
+        match result {
+            Ok(auth) => assert_eq!(auth.root_issuer, root_did),
+            Err(e) => panic!("expected wildcard-rooted chain to authorize, got error: {e}"),
+        }
+    }
+
+    #[test]
+    fn test_authorize_rejects_root_forging_capability_for_resource_it_does_not_own() {
+        use ed25519_dalek::SigningKey;
+
+        // `weather_key` is a legitimately whitelisted root for "weather-api",
+        // but it self-signs a capability for "finance-tools" — a resource it
+        // was never granted. The owning entry for "finance-tools" is some
+        // other DID entirely, so this must be rejected even though
+        // `weather_key`'s DID is a valid root issuer for *something*.
+        let weather_key = SigningKey::from_bytes(&[3u8; 32]);
+        let weather_did = did_key_for(&weather_key.verifying_key());
+        let finance_owner_did = did_key_for(&SigningKey::from_bytes(&[4u8; 32]).verifying_key());
+        let gateway_did = "did:key:zGatewayPlaceholder".to_string();
+
+        let forged_att = vec![Capability {
+            with: "mcp://finance-tools/*".into(),
+            can: "mcp/invoke".into(),
+        }];
+        let forged_token = sign_token(
+            &weather_key,
+            &weather_did,
+            &gateway_did,
+            9_999_999_999,
+            &forged_att,
+            &[],
+        );
+
+        let result = authorize(
+            &forged_token,
+            "mcp://finance-tools/read",
+            "mcp/invoke",
+            &gateway_did,
+            0,
+            Some(&finance_owner_did),
+        );
+        match result {
+            Ok(_) => panic!("root not owning the resource's namespace must not authorize"),
+            Err(e) => assert!(
+                e.contains("does not own resource"),
+                "expected an ownership-related denial, got: {e}"
+            ),
+        }
+    }
+}